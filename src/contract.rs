@@ -1,271 +1,2367 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
-    coins, to_binary, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Uint128,
+    coins, from_json, to_json_binary, Addr, BankMsg, Binary, BlockInfo, CosmosMsg, Decimal, Deps,
+    DepsMut, Empty, Env, HexBinary, MessageInfo, Order, Response, StdResult, Storage, Uint128,
+    WasmMsg,
 };
 use cosmwasm_std::{entry_point, StdError};
-use cw2::set_contract_version;
-use std::ops::Mul;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
+use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
+use cw_storage_plus::Bound;
+use cw_utils::{Duration, Expiration, Scheduled};
+use semver::Version;
+use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
-use crate::msg::{BidResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Config, State, Status, BIDS, CONFIG, STATE};
-
-const COMMISSION: u128 = 0.05 as u128; // 5%
+use crate::migrations;
+use crate::msg::{
+    AllBidsResponse, AuctionMsg, AuctionResponse, BidHistoryResponse, BidResponse, Cw20HookMsg,
+    Cw721HookMsg, DeniedAddressesResponse, ExecuteMsg, FeeScheduleResponse, InstantiateMsg,
+    ListAuctionsResponse, MigrateMsg, OrderBy, OwnershipResponse, PauseStateResponse, QueryMsg,
+    SudoMsg,
+};
+use crate::state::{
+    bid_history, bids, AuctionKind, BidIncrement, BidRecord, Cancellation, Commitment, Config,
+    DenomRate, FeeMode, Lot, Operation, Ownership, Settlement, StandingBid, State, Status,
+    AUCTION_COUNT, COMMITMENTS, CONFIGS, DENIED, ESCROWS, FEE_CAP, LOTS, OWNERSHIPS, PAUSED,
+    PAUSER, STATES,
+};
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    info: MessageInfo,
+    _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    AUCTION_COUNT.save(deps.storage, &0)?;
+
+    if let Some(pauser) = msg.pauser {
+        PAUSER.save(deps.storage, &deps.api.addr_validate(&pauser)?)?;
+    }
+
+    Ok(Response::new().add_attribute("action", "instantiate"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongContract {
+            expected: CONTRACT_NAME.to_string(),
+            found: stored.contract,
+        });
+    }
+
+    let stored_version = parse_version(&stored.version)?;
+
+    if stored_version > parse_version(CONTRACT_VERSION)? {
+        return Err(ContractError::CannotDowngrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    if stored_version < Version::new(0, 2, 0) {
+        migrations::migrate_to_0_2_0(deps.storage)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    use SudoMsg::{Cancel, ForceClose, ReassignOwner, SetFeeCap, SetPauser};
+
+    match msg {
+        ForceClose { auction_id } => sudo_force_close(deps, env, auction_id),
+        Cancel { auction_id } => sudo_cancel(deps, auction_id),
+        SetFeeCap { fee_cap } => sudo_set_fee_cap(deps, fee_cap),
+        ReassignOwner {
+            auction_id,
+            new_owner,
+        } => sudo_reassign_owner(deps, auction_id, new_owner),
+        SetPauser { pauser } => sudo_set_pauser(deps, pauser),
+    }
+}
+
+pub fn sudo_force_close(
+    deps: DepsMut,
+    env: Env,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    let state = STATES.load(deps.storage, auction_id)?;
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    if state.current_status != Status::Open {
+        return Err(ContractError::BiddingClosed);
+    }
+
+    // Closing a sealed auction early would forfeit commitments that never got to be revealed
+    if let AuctionKind::Sealed { .. } = config.kind {
+        if !bidding_end(&config, &state)?.is_expired(&env.block) {
+            return Err(ContractError::BiddingActive);
+        }
+    }
+
+    let resp = finish_auction(deps, auction_id, None)?
+        .add_attribute("action", "sudo_force_close")
+        .add_attribute("auction_id", auction_id.to_string());
+
+    Ok(resp)
+}
+
+pub fn sudo_cancel(deps: DepsMut, auction_id: u64) -> Result<Response, ContractError> {
+    // Bidders are paid back in full through `Retract` or the paged `RefundLosers`, refunding
+    // them all here could run out of gas on an auction with enough bids
+    let resp = cancel_auction(deps.storage, auction_id)?;
+
+    Ok(resp
+        .add_attribute("action", "sudo_cancel")
+        .add_attribute("auction_id", auction_id.to_string()))
+}
+
+pub fn sudo_set_fee_cap(deps: DepsMut, fee_cap: Decimal) -> Result<Response, ContractError> {
+    if fee_cap > Decimal::one() {
+        return Err(ContractError::InvalidCommission);
+    }
+
+    FEE_CAP.save(deps.storage, &fee_cap)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sudo_set_fee_cap")
+        .add_attribute("fee_cap", fee_cap.to_string()))
+}
+
+pub fn sudo_reassign_owner(
+    deps: DepsMut,
+    auction_id: u64,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+
+    CONFIGS.update(deps.storage, auction_id, |config| -> StdResult<_> {
+        let mut config = config.ok_or_else(|| StdError::not_found("Config"))?;
+        config.owner = new_owner.clone();
+        Ok(config)
+    })?;
+    OWNERSHIPS.remove(deps.storage, auction_id);
+
+    Ok(Response::new()
+        .add_attribute("action", "sudo_reassign_owner")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("owner", new_owner.as_str()))
+}
+
+pub fn sudo_set_pauser(deps: DepsMut, pauser: Option<String>) -> Result<Response, ContractError> {
+    let resp = Response::new().add_attribute("action", "sudo_set_pauser");
+
+    match pauser {
+        Some(pauser) => {
+            let pauser = deps.api.addr_validate(&pauser)?;
+            PAUSER.save(deps.storage, &pauser)?;
+
+            Ok(resp.add_attribute("pauser", pauser.as_str()))
+        }
+        None => {
+            PAUSER.remove(deps.storage);
+            PAUSED.remove(deps.storage);
+
+            Ok(resp)
+        }
+    }
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    version
+        .parse()
+        .map_err(|err: semver::Error| StdError::generic_err(err.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    use ExecuteMsg::{
+        AcceptOwnership, Allow, Bid, Cancel, Close, Commit, CreateAuction, Deny, Pause,
+        ProposeOwner, Receive, ReceiveNft, RefundLosers, RenounceOwnership, Retract, Reveal,
+        Settle, Unpause, UpdateAllowlist, UpdateConfig,
+    };
+
+    match msg {
+        CreateAuction(msg) => create_auction(deps, env, info, msg),
+        Receive(msg) => receive_cw20(deps, env, info, msg),
+        ReceiveNft(msg) => receive_nft(deps, info, msg),
+        Bid { auction_id, proof } => bid(deps, env, info, auction_id, proof),
+        Close { auction_id } => close(deps, env, info, auction_id),
+        UpdateConfig {
+            auction_id,
+            denom,
+            commission,
+            fee_mode,
+            fee_recipient,
+        } => update_config(
+            deps,
+            info,
+            auction_id,
+            denom,
+            commission,
+            fee_mode,
+            fee_recipient,
+        ),
+        ProposeOwner {
+            auction_id,
+            new_owner,
+        } => propose_owner(deps, info, auction_id, new_owner),
+        AcceptOwnership { auction_id } => accept_ownership(deps, info, auction_id),
+        RenounceOwnership { auction_id } => renounce_ownership(deps, info, auction_id),
+        UpdateAllowlist { auction_id, root } => update_allowlist(deps, info, auction_id, root),
+        Deny {
+            auction_id,
+            addresses,
+        } => set_denied(deps, info, auction_id, addresses, true),
+        Allow {
+            auction_id,
+            addresses,
+        } => set_denied(deps, info, auction_id, addresses, false),
+        Cancel { auction_id } => cancel(deps, env, info, auction_id),
+        Pause { operation } => set_paused(deps, info, operation, true),
+        Unpause { operation } => set_paused(deps, info, operation, false),
+        RefundLosers {
+            auction_id,
+            start_after,
+            limit,
+        } => refund_losers(deps, auction_id, start_after, limit),
+        Commit {
+            auction_id,
+            commitment,
+            proof,
+        } => commit(deps, env, info, auction_id, commitment, proof),
+        Reveal {
+            auction_id,
+            amount,
+            salt,
+        } => reveal(deps, env, info, auction_id, amount, salt),
+        Settle { auction_id } => settle(deps, env, info, auction_id),
+        Retract {
+            auction_id,
+            receiver,
+        } => retract(deps, info, auction_id, receiver),
+    }
+}
+
+pub fn create_auction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: AuctionMsg,
+) -> Result<Response, ContractError> {
     let commission = msg.commission.unwrap_or_default();
+    let end_time = msg.end_time.unwrap_or_default();
+    let keeper_reward = msg.keeper_reward.unwrap_or_default();
+    // Sealed bids are only known once revealed, so they pay their fee at settlement
+    let fee_mode = match (&msg.kind, msg.fee_mode) {
+        (Some(AuctionKind::Sealed { .. }), Some(FeeMode::PerBid)) => {
+            return Err(ContractError::InvalidFeeMode);
+        }
+        (Some(AuctionKind::Sealed { .. }), None) => FeeMode::OnSettlement,
+        (_, fee_mode) => fee_mode.unwrap_or_default(),
+    };
+
+    if commission > fee_cap(deps.storage)? {
+        return Err(ContractError::InvalidCommission);
+    }
+
+    if keeper_reward > Decimal::one() {
+        return Err(ContractError::InvalidKeeperReward);
+    }
+
+    let invalid_increment = match &msg.min_increment {
+        Some(BidIncrement::Amount(amount)) => amount.is_zero(),
+        Some(BidIncrement::Percent(percent)) => percent.is_zero() || *percent > Decimal::one(),
+        None => false,
+    };
+    if invalid_increment {
+        return Err(ContractError::InvalidIncrement);
+    }
+
+    // Keepers are paid out of the fee charged at settlement
+    if !keeper_reward.is_zero() && fee_mode == FeeMode::PerBid {
+        return Err(ContractError::InvalidKeeperReward);
+    }
+
+    if end_time.is_expired(&env.block) {
+        return Err(ContractError::InvalidSchedule);
+    }
 
-    STATE.save(
+    let ends_before_start = match (msg.start_time, end_time) {
+        (Some(Scheduled::AtTime(start)), Expiration::AtTime(end)) => end <= start,
+        (Some(Scheduled::AtHeight(start)), Expiration::AtHeight(end)) => end <= start,
+        _ => false,
+    };
+    if ends_before_start {
+        return Err(ContractError::InvalidSchedule);
+    }
+
+    if let Some(buy_now) = msg.buy_now {
+        let floor = msg.reserve_price.max(msg.opening_price).unwrap_or_default();
+
+        if buy_now.is_zero() || buy_now < floor {
+            return Err(ContractError::InvalidBuyNow);
+        }
+    }
+
+    let kind = msg.kind.unwrap_or_default();
+    let settlement = msg.settlement.unwrap_or_default();
+    let mut start_time = msg.start_time;
+
+    // Neither a dutch auction nor a buy now price leaves a runner-up to set the price
+    if settlement == Settlement::SecondPrice {
+        if let AuctionKind::Dutch { .. } = kind {
+            return Err(ContractError::InvalidDutchAuction);
+        }
+
+        if msg.buy_now.is_some() {
+            return Err(ContractError::InvalidBuyNow);
+        }
+    }
+
+    if let AuctionKind::Dutch {
+        start_price,
+        floor_price,
+        step,
+    } = &kind
+    {
+        if start_price <= floor_price || step == &Some(0) {
+            return Err(ContractError::InvalidDutchAuction);
+        }
+
+        if msg.buy_now.is_some() || msg.reserve_price > Some(*floor_price) {
+            return Err(ContractError::InvalidDutchAuction);
+        }
+
+        // The price curve runs on block time from the start to the end of the auction
+        start_time = match (start_time, end_time) {
+            (Some(Scheduled::AtTime(start)), Expiration::AtTime(_)) => {
+                Some(Scheduled::AtTime(start))
+            }
+            (None, Expiration::AtTime(_)) => Some(Scheduled::AtTime(env.block.time)),
+            _ => return Err(ContractError::InvalidSchedule),
+        };
+    }
+
+    if let AuctionKind::Sealed {
+        reveal_period,
+        forfeit,
+    } = &kind
+    {
+        if *forfeit > Decimal::one() {
+            return Err(ContractError::InvalidForfeit);
+        }
+
+        if msg.buy_now.is_some() {
+            return Err(ContractError::InvalidBuyNow);
+        }
+
+        // Deposits backing hidden bids are held in the auction denom only
+        if msg
+            .accepted_denoms
+            .as_ref()
+            .is_some_and(|denoms| !denoms.is_empty())
+        {
+            return Err(ContractError::InvalidDenoms);
+        }
+
+        let reveal_mismatch = !matches!(
+            (reveal_period, end_time),
+            (Duration::Time(_), Expiration::AtTime(_))
+                | (Duration::Height(_), Expiration::AtHeight(_))
+        );
+        if reveal_mismatch {
+            return Err(ContractError::InvalidSchedule);
+        }
+    }
+
+    let window_mismatch = matches!(
+        (msg.extension_window, end_time),
+        (Some(Duration::Time(_)), Expiration::AtHeight(_))
+            | (Some(Duration::Height(_)), Expiration::AtTime(_))
+    );
+    if window_mismatch {
+        return Err(ContractError::InvalidSchedule);
+    }
+
+    let cancellation = msg.cancellation.unwrap_or_default();
+    let cancel_window_mismatch = matches!(
+        (&cancellation, end_time),
+        (
+            Cancellation::BeforeEnd(Duration::Time(_)),
+            Expiration::AtHeight(_)
+        ) | (
+            Cancellation::BeforeEnd(Duration::Height(_)),
+            Expiration::AtTime(_)
+        )
+    );
+    if cancel_window_mismatch {
+        return Err(ContractError::InvalidSchedule);
+    }
+
+    if msg.allowlist.as_ref().is_some_and(|root| root.len() != 32) {
+        return Err(ContractError::InvalidAllowlist);
+    }
+
+    let accepted_denoms = msg.accepted_denoms.unwrap_or_default();
+    validate_accepted_denoms(&msg.denom, &accepted_denoms)?;
+
+    let auction_id =
+        AUCTION_COUNT.update(deps.storage, |count| -> StdResult<_> { Ok(count + 1) })?;
+
+    STATES.save(
         deps.storage,
+        auction_id,
         &State {
             current_status: Status::Open,
             highest_bid: None,
+            runner_up: None,
+            end_time,
         },
     )?;
 
     let owner = match msg.owner {
         Some(owner) => deps.api.addr_validate(&owner)?,
-        None => info.sender,
+        None => info.sender.clone(),
     };
 
-    CONFIG.save(
+    let fee_recipient = msg
+        .fee_recipient
+        .map(|fee_recipient| deps.api.addr_validate(&fee_recipient))
+        .transpose()?;
+
+    if let Denom::Cw20(address) = &msg.denom {
+        deps.api.addr_validate(address.as_str())?;
+    }
+
+    if let Some(lot) = msg.lot {
+        let lot = Lot {
+            collection: deps.api.addr_validate(&lot.collection)?,
+            token_id: lot.token_id,
+            escrowed: false,
+        };
+
+        LOTS.save(deps.storage, auction_id, &lot)?;
+    }
+
+    CONFIGS.save(
         deps.storage,
+        auction_id,
         &Config {
             owner,
             denom: msg.denom,
             commission,
+            fee_mode,
+            fee_recipient,
+            reserve_price: msg.reserve_price,
+            opening_price: msg.opening_price.unwrap_or_default(),
+            min_increment: msg.min_increment,
+            buy_now: msg.buy_now,
+            kind,
+            settlement,
+            start_time,
+            end_time,
+            extension_window: msg.extension_window,
+            keeper_reward,
+            cancellation,
+            allowlist: msg.allowlist,
+            accepted_denoms,
         },
     )?;
 
-    Ok(Response::new().add_attribute("action", "instantiate"))
+    Ok(Response::new()
+        .add_attribute("action", "create_auction")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("auction_id", auction_id.to_string()))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(
+pub fn bid(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    msg: ExecuteMsg,
+    auction_id: u64,
+    proof: Option<Vec<HexBinary>>,
 ) -> Result<Response, ContractError> {
-    use ExecuteMsg::{Bid, Close, Retract};
+    let funds = bid_funds(deps.as_ref(), &info, auction_id)?;
 
-    match msg {
-        Bid {} => bid(deps, info),
-        Close {} => close(deps, info),
-        Retract { receiver } => retract(deps, info, receiver),
+    place_bid(deps, env, info.sender, auction_id, funds, proof)
+}
+
+pub fn commit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: u64,
+    commitment: HexBinary,
+    proof: Option<Vec<HexBinary>>,
+) -> Result<Response, ContractError> {
+    let deposit = native_funds(deps.as_ref(), &info, auction_id)?;
+
+    place_commitment(
+        deps,
+        env,
+        info.sender,
+        auction_id,
+        commitment,
+        deposit,
+        proof,
+    )
+}
+
+/// Coins sent along with a bid, every one of them in the auction denom or an accepted one
+fn bid_funds(
+    deps: Deps,
+    info: &MessageInfo,
+    auction_id: u64,
+) -> Result<Vec<(Denom, Uint128)>, ContractError> {
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    let funds: Vec<_> = info
+        .funds
+        .iter()
+        .map(|coin| (Denom::Native(coin.denom.clone()), coin.amount))
+        .collect();
+
+    let accepted = funds
+        .iter()
+        .all(|(denom, _)| *denom == config.denom || accepted_denom(&config, denom).is_some());
+    if funds.is_empty() || !accepted {
+        return Err(ContractError::InvalidFunds);
+    }
+
+    Ok(funds)
+}
+
+/// Amount of the auction's native denom sent along with the message
+fn native_funds(deps: Deps, info: &MessageInfo, auction_id: u64) -> Result<Uint128, ContractError> {
+    let denom = CONFIGS.load(deps.storage, auction_id)?.denom;
+
+    let funds = match denom {
+        Denom::Native(denom) => info.funds.iter().find(|coin| coin.denom == denom),
+        Denom::Cw20(_) => None,
+    };
+
+    match funds {
+        Some(funds) => Ok(funds.amount),
+        None => Err(ContractError::InvalidFunds),
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let hook: Cw20HookMsg = from_json(&msg.msg)?;
+    let auction_id = match hook {
+        Cw20HookMsg::Bid { auction_id, .. } | Cw20HookMsg::Commit { auction_id, .. } => auction_id,
+    };
+
+    let denom = CONFIGS.load(deps.storage, auction_id)?.denom;
+    if denom != Denom::Cw20(info.sender) {
+        return Err(ContractError::InvalidFunds);
+    }
+
+    let sender = deps.api.addr_validate(&msg.sender)?;
+
+    match hook {
+        Cw20HookMsg::Bid { auction_id, proof } => place_bid(
+            deps,
+            env,
+            sender,
+            auction_id,
+            vec![(denom, msg.amount)],
+            proof,
+        ),
+        Cw20HookMsg::Commit {
+            auction_id,
+            commitment,
+            proof,
+        } => place_commitment(deps, env, sender, auction_id, commitment, msg.amount, proof),
     }
 }
 
-pub fn bid(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
-    let owner = CONFIG.load(deps.storage)?.owner;
-    let denom = CONFIG.load(deps.storage)?.denom;
-    let commission = Uint128::new(CONFIG.load(deps.storage)?.commission);
+fn place_bid(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    auction_id: u64,
+    funds: Vec<(Denom, Uint128)>,
+    proof: Option<Vec<HexBinary>>,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::Bid)?;
+
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+    let mut state = STATES.load(deps.storage, auction_id)?;
+    let min_bid = current_price(&config, &state, &env.block)?;
+    let fee_rate = fee_rate(deps.storage, &config)?;
+
+    if let AuctionKind::Sealed { .. } = config.kind {
+        return Err(ContractError::WrongAuctionKind);
+    }
+
+    let owner = &config.owner;
+    let fee_recipient = config.fee_recipient.as_ref().unwrap_or(owner);
 
-    let mut state = STATE.load(deps.storage)?;
     let highest_bid = state.highest_bid;
 
     let mut resp = Response::new();
 
-    if state.current_status == Status::Closed {
+    if state.current_status != Status::Open || state.end_time.is_expired(&env.block) {
         return Err(ContractError::BiddingClosed);
     }
 
-    if owner == info.sender {
+    if let Some(start_time) = config.start_time {
+        if !start_time.is_triggered(&env.block) {
+            return Err(ContractError::BiddingNotStarted);
+        }
+    }
+
+    if *owner == sender {
         return Err(ContractError::UnauthorizedBid {
             owner: owner.to_string(),
         });
     }
 
-    let highest_bid_amount = match highest_bid {
-        Some(highest_bid) => highest_bid.1,
+    ensure_not_denied(deps.storage, auction_id, &sender)?;
+    ensure_allowlisted(&config.allowlist, &sender, proof)?;
+
+    if let Some(lot) = LOTS.may_load(deps.storage, auction_id)? {
+        if !lot.escrowed {
+            return Err(ContractError::LotNotEscrowed);
+        }
+    }
+
+    let highest_bid_amount = match &highest_bid {
+        Some(highest_bid) => highest_bid.amount,
         None => Uint128::new(0),
     };
 
-    let funds = match info.funds.iter().find(|coin| coin.denom == denom) {
-        Some(funds) => funds.amount,
-        None => return Err(ContractError::InvalidFunds),
+    // The first bid paying the live price of a dutch auction takes the lot
+    let buy_now = match config.kind {
+        AuctionKind::Dutch { .. } => Some(min_bid),
+        _ => config.buy_now,
     };
 
-    if !commission.is_zero() && funds < commission {
-        return Err(ContractError::InvalidCommission { funds, commission });
-    }
+    // Each coin pays commission in its own denom and counts for its value in the auction denom
+    let mut gross = Uint128::zero();
+    let mut net_bid = Uint128::zero();
+    let mut deposits = vec![];
+    let mut commissions = vec![];
+
+    for (denom, amount) in funds {
+        let commission = match config.fee_mode {
+            FeeMode::PerBid => amount.checked_mul_floor(fee_rate)?,
+            FeeMode::OnSettlement => Uint128::zero(),
+        };
+        let net = amount.checked_sub(commission)?;
 
-    let comm = Uint128::from(COMMISSION);
-    let commission = funds.mul(comm);
+        if !commission.is_zero() {
+            commissions.push((denom.clone(), commission));
+        }
 
-    let net_bid = funds - commission;
+        match accepted_denom(&config, &denom) {
+            Some(accepted) => {
+                let held = ESCROWS
+                    .may_load(deps.storage, (auction_id, &sender, &accepted.denom))?
+                    .unwrap_or_default();
+                let total = held.checked_add(net)?;
+                let added = total
+                    .checked_mul_floor(accepted.rate)?
+                    .checked_sub(held.checked_mul_floor(accepted.rate)?)?;
 
-    let existing_bid = match BIDS.may_load(deps.storage, &info.sender)? {
+                gross = gross.checked_add(amount.checked_mul_floor(accepted.rate)?)?;
+                net_bid = net_bid.checked_add(added)?;
+                deposits.push((accepted.denom.as_str(), total));
+            }
+            None => {
+                gross = gross.checked_add(amount)?;
+                net_bid = net_bid.checked_add(net)?;
+            }
+        }
+    }
+
+    let existing_bid = match bids().may_load(deps.storage, (auction_id, &sender))? {
         Some(existing_bid) => existing_bid,
         None => Uint128::new(0),
     };
 
-    let new_bid = net_bid + existing_bid;
-    if new_bid <= highest_bid_amount {
+    let new_bid = net_bid.checked_add(existing_bid)?;
+    if new_bid < min_bid {
         return Err(ContractError::InvalidBid {
             existing: existing_bid,
-            funds,
+            funds: gross,
             new_bid: net_bid,
             max_bid: highest_bid_amount,
+            min_bid,
         });
     }
 
-    state.highest_bid = Some((info.sender.clone(), new_bid));
+    if let Some(highest_bid) = highest_bid {
+        if highest_bid.bidder != sender {
+            state.runner_up = Some(highest_bid);
+        }
+    }
+
+    state.highest_bid = Some(StandingBid {
+        bidder: sender.clone(),
+        amount: new_bid,
+    });
 
-    if !commission.is_zero() {
-        let funds: Vec<_> = coins(commission.u128(), denom);
+    if let Some(window) = config.extension_window {
+        let end_time = extend_end_time(state.end_time, window, &env.block);
+
+        if end_time != state.end_time {
+            state.end_time = end_time;
+            resp = resp.add_attribute("end_time_extended", end_time.to_string());
+        }
+    }
+
+    for (denom, total) in deposits {
+        ESCROWS.save(deps.storage, (auction_id, &sender, denom), &total)?;
+    }
+    bids().save(deps.storage, (auction_id, &sender), &new_bid)?;
+
+    let buy_now = buy_now.filter(|buy_now| new_bid >= *buy_now);
+
+    if let Some(buy_now) = buy_now {
+        state.highest_bid = Some(StandingBid {
+            bidder: sender.clone(),
+            amount: buy_now,
+        });
+    }
 
-        let bank_msg = BankMsg::Send {
-            to_address: owner.into_string(),
-            amount: funds,
+    // Only the part of the bid going into the price pays commission, whatever is sent back as
+    // change gets its share of the commission back as well
+    for (denom, commission) in commissions {
+        let charged = match buy_now {
+            Some(buy_now) => {
+                commission.multiply_ratio(buy_now.saturating_sub(existing_bid), net_bid)
+            }
+            None => commission,
         };
 
-        resp = resp
-            .add_message(bank_msg)
-            .add_attribute("commission_to_owner", info.sender.as_str());
+        if !charged.is_zero() {
+            resp = resp
+                .add_message(send_tokens(&denom, fee_recipient, charged)?)
+                .add_attribute("commission", charged);
+        }
+
+        if charged < commission {
+            resp = resp.add_message(send_tokens(&denom, &sender, commission - charged)?);
+        }
     }
 
-    BIDS.update(deps.storage, &info.sender, |_| -> StdResult<_> {
-        Ok(new_bid)
-    })?;
+    record_bid(
+        deps.storage,
+        &env.block,
+        auction_id,
+        &sender,
+        net_bid,
+        new_bid,
+    )?;
 
-    STATE.save(deps.storage, &state)?;
+    STATES.save(deps.storage, auction_id, &state)?;
 
     resp = resp
         .add_attribute("action", "bid")
-        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", sender.as_str())
         .add_attribute("current_highest_bid", new_bid);
 
-    Ok(resp)
-}
-
-pub fn close(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
-    let state = STATE.load(deps.storage)?;
-    let owner = CONFIG.load(deps.storage)?.owner;
-    let denom = CONFIG.load(deps.storage)?.denom;
+    if buy_now.is_some() {
+        let finished = finish_auction(deps.branch(), auction_id, None)?;
 
-    let mut resp = Response::new();
+        resp = resp
+            .add_submessages(finished.messages)
+            .add_attributes(finished.attributes)
+            .add_attribute("buy_now", sender.as_str());
 
-    if state.current_status == Status::Closed {
-        return Err(ContractError::BiddingClosed);
+        // The price is paid, whatever the winner sent on top goes straight back
+        if bids().has(deps.storage, (auction_id, &sender)) {
+            let change = refund_bid(deps.storage, &config, auction_id, &sender, &sender)?;
+            resp = resp.add_submessages(change.messages);
+        }
     }
 
-    if owner != info.sender {
-        return Err(ContractError::Unauthorized {
-            owner: owner.to_string(),
-        });
+    Ok(resp)
+}
+
+/// The entry for `denom` among the auction's accepted denoms; `None` for the auction denom itself
+fn accepted_denom<'a>(config: &'a Config, denom: &Denom) -> Option<&'a DenomRate> {
+    match denom {
+        Denom::Native(denom) => config
+            .accepted_denoms
+            .iter()
+            .find(|accepted| accepted.denom == *denom),
+        Denom::Cw20(_) => None,
     }
+}
 
-    if let Some(highest_bid) = state.highest_bid {
-        let funds: Vec<_> = coins(highest_bid.1.u128(), denom);
+/// Every accepted denom needs a rate and has to differ from the auction denom and the others
+fn validate_accepted_denoms(
+    denom: &Denom,
+    accepted_denoms: &[DenomRate],
+) -> Result<(), ContractError> {
+    let mut seen = vec![denom.clone()];
 
-        let bank_msg = BankMsg::Send {
-            to_address: owner.into_string(),
-            amount: funds,
-        };
+    for accepted in accepted_denoms {
+        let accepted_denom = Denom::Native(accepted.denom.clone());
 
-        BIDS.remove(deps.storage, &highest_bid.0);
+        if accepted.rate.is_zero() || seen.contains(&accepted_denom) {
+            return Err(ContractError::InvalidDenoms);
+        }
 
-        resp = resp
-            .add_message(bank_msg)
-            .add_attribute("Highest_bid", highest_bid.0.as_str());
+        seen.push(accepted_denom);
     }
 
-    STATE.update(deps.storage, |mut state| -> StdResult<_> {
-        state.current_status = Status::Closed;
-        Ok(state)
-    })?;
-
-    resp = resp
-        .add_attribute("action", "close")
-        .add_attribute("sender", info.sender.as_str());
-
-    Ok(resp)
+    Ok(())
 }
 
-pub fn retract(
-    deps: DepsMut,
-    info: MessageInfo,
-    receiver: Option<String>,
-) -> Result<Response, ContractError> {
-    let status = STATE.load(deps.storage)?.current_status;
+/// Takes `value`, counted in the auction denom, out of the escrow of `bidder` and returns the
+/// coins taken. The auction denom is used up first, then the accepted denoms in their listed
+/// order, each rounded up so the coins are worth at least `value`. Once nothing of value is
+/// left, any dust left over is taken along.
+fn withdraw_escrow(
+    storage: &mut dyn Storage,
+    config: &Config,
+    auction_id: u64,
+    bidder: &Addr,
+    value: Uint128,
+) -> Result<Vec<(Denom, Uint128)>, ContractError> {
+    let total = bids()
+        .may_load(storage, (auction_id, bidder))?
+        .unwrap_or_default();
 
-    if status == Status::Open {
-        return Err(ContractError::BiddingActive);
+    let mut held = vec![];
+    let mut held_value = Uint128::zero();
+    for accepted in &config.accepted_denoms {
+        if let Some(amount) = ESCROWS.may_load(storage, (auction_id, bidder, &accepted.denom))? {
+            held_value = held_value.checked_add(amount.checked_mul_floor(accepted.rate)?)?;
+            held.push((accepted, amount));
+        }
     }
 
-    let funds = match BIDS.load(deps.storage, &info.sender) {
-        Ok(amount) => coins(amount.u128(), amount),
-        _ => return Err(ContractError::InvalidRetract),
-    };
+    let base = total.checked_sub(held_value)?;
+    let from_base = value.min(base);
+    let mut remaining = value - from_base;
+    let mut left = base - from_base;
+    let mut split = vec![];
 
-    let receiver = match receiver {
-        Some(receiver) => deps.api.addr_validate(&receiver)?,
-        None => info.sender.clone(),
-    };
+    for (accepted, amount) in held {
+        let taken = remaining.checked_div_ceil(accepted.rate)?.min(amount);
+        let rest = amount - taken;
+        let rest_value = rest.checked_mul_floor(accepted.rate)?;
+        let taken_value = amount.checked_mul_floor(accepted.rate)? - rest_value;
 
-    let bank_msg = BankMsg::Send {
-        to_address: receiver.to_string(),
-        amount: funds,
-    };
+        remaining = remaining.saturating_sub(taken_value);
+        left = left.checked_add(rest_value)?;
+        split.push((accepted, taken, rest));
+    }
 
-    BIDS.remove(deps.storage, &receiver);
+    let mut coins = vec![];
+    if !from_base.is_zero() {
+        coins.push((config.denom.clone(), from_base));
+    }
 
-    Ok(Response::new().add_message(bank_msg).add_attributes(vec![
-        ("action", "retract"),
-        ("sender", info.sender.as_str()),
-        ("retract_funds_to_receiver", receiver.as_str()),
-    ]))
-}
+    for (accepted, mut taken, rest) in split {
+        let key = (auction_id, bidder, accepted.denom.as_str());
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::TotalBids { address } => to_binary(&self::total_bids(deps, address)?),
-        QueryMsg::HighestBid {} => to_binary(&self::highest_bid(deps)?),
-        QueryMsg::BiddingCompleted {} => to_binary(&self::bidding_completed(deps)?),
-        QueryMsg::WinningBid {} => to_binary(&self::winning_bid(deps)?),
+        if left.is_zero() || rest.is_zero() {
+            taken += rest;
+            ESCROWS.remove(storage, key);
+        } else {
+            ESCROWS.save(storage, key, &rest)?;
+        }
+
+        if !taken.is_zero() {
+            coins.push((Denom::Native(accepted.denom.clone()), taken));
+        }
     }
-}
 
-pub fn total_bids(deps: Deps, address: String) -> StdResult<Uint128> {
-    let address = deps.api.addr_validate(&address)?;
+    if left.is_zero() {
+        bids().remove(storage, (auction_id, bidder))?;
+    } else {
+        bids().save(storage, (auction_id, bidder), &left)?;
+    }
 
-    BIDS.load(deps.storage, &address)
+    Ok(coins)
 }
 
-pub fn highest_bid(deps: Deps) -> StdResult<BidResponse> {
-    match STATE.load(deps.storage)?.highest_bid {
-        Some((address, amount)) => Ok(BidResponse { address, amount }),
-        None => Err(StdError::not_found("Auction has no bid")),
+fn place_commitment(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    auction_id: u64,
+    commitment: HexBinary,
+    deposit: Uint128,
+    proof: Option<Vec<HexBinary>>,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::Bid)?;
+
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+    let state = STATES.load(deps.storage, auction_id)?;
+
+    if !matches!(config.kind, AuctionKind::Sealed { .. }) {
+        return Err(ContractError::WrongAuctionKind);
     }
-}
 
-pub fn bidding_completed(deps: Deps) -> StdResult<bool> {
-    match STATE.load(deps.storage)?.current_status {
-        Status::Closed => Ok(true),
-        _ => Ok(false),
+    if state.current_status != Status::Open || state.end_time.is_expired(&env.block) {
+        return Err(ContractError::BiddingClosed);
     }
-}
 
-pub fn winning_bid(deps: Deps) -> StdResult<BidResponse> {
-    if STATE.load(deps.storage)?.current_status == Status::Open {
-        return Err(StdError::generic_err("Auction is closed"));
+    if let Some(start_time) = config.start_time {
+        if !start_time.is_triggered(&env.block) {
+            return Err(ContractError::BiddingNotStarted);
+        }
     }
-    match STATE.load(deps.storage)?.highest_bid {
-        Some((address, amount)) => Ok(BidResponse { address, amount }),
+
+    if config.owner == sender {
+        return Err(ContractError::UnauthorizedBid {
+            owner: config.owner.to_string(),
+        });
+    }
+
+    ensure_not_denied(deps.storage, auction_id, &sender)?;
+    ensure_allowlisted(&config.allowlist, &sender, proof)?;
+
+    if let Some(lot) = LOTS.may_load(deps.storage, auction_id)? {
+        if !lot.escrowed {
+            return Err(ContractError::LotNotEscrowed);
+        }
+    }
+
+    if commitment.len() != 32 || COMMITMENTS.has(deps.storage, (auction_id, &sender)) {
+        return Err(ContractError::InvalidCommitment);
+    }
+
+    COMMITMENTS.save(
+        deps.storage,
+        (auction_id, &sender),
+        &Commitment {
+            commitment,
+            revealed: false,
+        },
+    )?;
+    bids().save(deps.storage, (auction_id, &sender), &deposit)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "commit")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", sender.as_str())
+        .add_attribute("deposit", deposit))
+}
+
+pub fn reveal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: u64,
+    amount: Uint128,
+    salt: String,
+) -> Result<Response, ContractError> {
+    // Not gated by the bid pause, the reveal window would run out and forfeit the deposits
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+    let mut state = STATES.load(deps.storage, auction_id)?;
+
+    if !matches!(config.kind, AuctionKind::Sealed { .. }) {
+        return Err(ContractError::WrongAuctionKind);
+    }
+
+    if state.current_status != Status::Open || bidding_end(&config, &state)?.is_expired(&env.block)
+    {
+        return Err(ContractError::BiddingClosed);
+    }
+
+    if !state.end_time.is_expired(&env.block) {
+        return Err(ContractError::BiddingActive);
+    }
+
+    ensure_not_denied(deps.storage, auction_id, &info.sender)?;
+
+    let mut commitment = match COMMITMENTS.may_load(deps.storage, (auction_id, &info.sender))? {
+        Some(commitment) if !commitment.revealed => commitment,
+        _ => return Err(ContractError::InvalidReveal),
+    };
+
+    if commitment.commitment != commitment_hash(&info.sender, amount, &salt) {
+        return Err(ContractError::InvalidReveal);
+    }
+
+    let deposit = bids().load(deps.storage, (auction_id, &info.sender))?;
+    if amount > deposit {
+        return Err(ContractError::InsufficientDeposit { deposit, amount });
+    }
+
+    let min_bid = current_price(&config, &state, &env.block)?;
+    if amount < min_bid {
+        return Err(ContractError::InvalidBid {
+            existing: deposit,
+            funds: Uint128::zero(),
+            new_bid: amount,
+            max_bid: Uint128::zero(),
+            min_bid,
+        });
+    }
+
+    commitment.revealed = true;
+    COMMITMENTS.save(deps.storage, (auction_id, &info.sender), &commitment)?;
+    record_bid(
+        deps.storage,
+        &env.block,
+        auction_id,
+        &info.sender,
+        amount,
+        amount,
+    )?;
+
+    let bid = StandingBid {
+        bidder: info.sender.clone(),
+        amount,
+    };
+
+    let beats = |other: &Option<StandingBid>| match other {
+        Some(other) => amount > other.amount,
+        None => true,
+    };
+
+    // Ties go to whoever revealed first
+    if beats(&state.highest_bid) {
+        state.runner_up = state.highest_bid.replace(bid);
+    } else if beats(&state.runner_up) {
+        state.runner_up = Some(bid);
+    }
+
+    STATES.save(deps.storage, auction_id, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reveal")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("amount", amount))
+}
+
+/// sha256 over the bidder address, the amount as 16 big endian bytes and the salt
+pub fn commitment_hash(bidder: &Addr, amount: Uint128, salt: &str) -> HexBinary {
+    let mut hasher = Sha256::new();
+    hasher.update(bidder.as_bytes());
+    hasher.update(amount.u128().to_be_bytes());
+    hasher.update(salt.as_bytes());
+
+    HexBinary::from(hasher.finalize().as_slice())
+}
+
+fn ensure_not_denied(
+    storage: &dyn Storage,
+    auction_id: u64,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    if DENIED.has(storage, (auction_id, sender)) {
+        return Err(ContractError::Denied {
+            address: sender.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+fn ensure_allowlisted(
+    root: &Option<HexBinary>,
+    sender: &Addr,
+    proof: Option<Vec<HexBinary>>,
+) -> Result<(), ContractError> {
+    match root {
+        Some(root) if !verify_proof(root, sender, &proof.unwrap_or_default()) => {
+            Err(ContractError::NotAllowlisted)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Walks from the address leaf up to `root`, hashing each pair of nodes in sorted order
+pub fn verify_proof(root: &HexBinary, address: &Addr, proof: &[HexBinary]) -> bool {
+    let leaf: [u8; 32] = Sha256::digest(address.as_bytes()).into();
+
+    let computed = proof.iter().try_fold(leaf, |node, sibling| {
+        let sibling: [u8; 32] = sibling.as_slice().try_into().ok()?;
+        let (left, right) = if node <= sibling {
+            (node, sibling)
+        } else {
+            (sibling, node)
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(left);
+        hasher.update(right);
+        Some(hasher.finalize().into())
+    });
+
+    computed.is_some_and(|computed: [u8; 32]| computed.as_slice() == root.as_slice())
+}
+
+pub fn close(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::Close)?;
+
+    let state = STATES.load(deps.storage, auction_id)?;
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    if state.current_status != Status::Open {
+        return Err(ContractError::BiddingClosed);
+    }
+
+    ensure_owner(deps.as_ref(), auction_id, &config, &info.sender)?;
+
+    // A sealed auction can only pick its winner once every bid had the chance to be revealed
+    if let AuctionKind::Sealed { .. } = config.kind {
+        if !bidding_end(&config, &state)?.is_expired(&env.block) {
+            return Err(ContractError::BiddingActive);
+        }
+    }
+
+    let resp = finish_auction(deps, auction_id, None)?
+        .add_attribute("action", "close")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", info.sender.as_str());
+
+    Ok(resp)
+}
+
+pub fn settle(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::Close)?;
+
+    let state = STATES.load(deps.storage, auction_id)?;
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    if state.current_status != Status::Open {
+        return Err(ContractError::BiddingClosed);
+    }
+
+    if !bidding_end(&config, &state)?.is_expired(&env.block) {
+        return Err(ContractError::BiddingActive);
+    }
+
+    let resp = finish_auction(deps, auction_id, Some(&info.sender))?
+        .add_attribute("action", "settle")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", info.sender.as_str());
+
+    Ok(resp)
+}
+
+/// Pays the highest bid out to the owner and closes the auction. When a keeper
+/// settles it, their share of the fee is split off before the fee recipient is paid.
+fn finish_auction(
+    deps: DepsMut,
+    auction_id: u64,
+    keeper: Option<&Addr>,
+) -> Result<Response, ContractError> {
+    let state = STATES.load(deps.storage, auction_id)?;
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    let mut resp = Response::new();
+
+    if let Some(reserve_price) = config.reserve_price {
+        let highest_bid_amount = match &state.highest_bid {
+            Some(highest_bid) => highest_bid.amount,
+            None => Uint128::zero(),
+        };
+
+        if highest_bid_amount < reserve_price {
+            STATES.update(deps.storage, auction_id, |state| -> StdResult<_> {
+                let mut state = state.ok_or_else(|| StdError::not_found("State"))?;
+                state.current_status = Status::Failed;
+                Ok(state)
+            })?;
+
+            let lot_msg = release_lot(deps.storage, auction_id, &config.owner)?;
+
+            return Ok(resp
+                .add_messages(lot_msg)
+                .add_attribute("reserve_price_met", "false"));
+        }
+    }
+
+    let lot_recipient = match &state.highest_bid {
+        Some(highest_bid) => &highest_bid.bidder,
+        None => &config.owner,
+    };
+    resp = resp.add_messages(release_lot(deps.storage, auction_id, lot_recipient)?);
+
+    if let Some(highest_bid) = &state.highest_bid {
+        let price = clearing_price(&config, &state);
+        let fee_rate = match config.fee_mode {
+            FeeMode::PerBid => Decimal::zero(),
+            FeeMode::OnSettlement => fee_rate(deps.storage, &config)?,
+        };
+        let keeper_reward = match keeper {
+            Some(_) => config.keeper_reward,
+            None => Decimal::zero(),
+        };
+        let fee_recipient = config.fee_recipient.as_ref().unwrap_or(&config.owner);
+
+        // Whatever the winner escrowed above the winning amount stays retractable
+        let payment = withdraw_escrow(
+            deps.storage,
+            &config,
+            auction_id,
+            &highest_bid.bidder,
+            price,
+        )?;
+
+        // The fee is taken from each coin paid and the keeper's reward from the fee, so the
+        // owner's proceeds never depend on who settles
+        for (denom, amount) in payment {
+            let fee = amount.checked_mul_floor(fee_rate)?;
+            let reward = fee.checked_mul_floor(keeper_reward)?;
+            let proceeds = amount.checked_sub(fee)?;
+
+            if let Some(keeper) = keeper.filter(|_| !reward.is_zero()) {
+                resp = resp.add_message(send_tokens(&denom, keeper, reward)?);
+            }
+
+            if fee > reward {
+                resp = resp.add_message(send_tokens(&denom, fee_recipient, fee - reward)?);
+            }
+
+            if !proceeds.is_zero() {
+                resp = resp.add_message(send_tokens(&denom, &config.owner, proceeds)?);
+            }
+        }
+
+        let fee = price.checked_mul_floor(fee_rate)?;
+        let reward = fee.checked_mul_floor(keeper_reward)?;
+
+        if config.fee_mode == FeeMode::OnSettlement {
+            resp = resp
+                .add_attribute("gross", price)
+                .add_attribute("fee", fee)
+                .add_attribute("net", price.checked_sub(fee)?);
+        }
+
+        if !reward.is_zero() {
+            resp = resp.add_attribute("keeper_reward", reward);
+        }
+
+        resp = resp
+            .add_attribute("Highest_bid", highest_bid.bidder.as_str())
+            .add_attribute("price", price);
+    }
+
+    STATES.update(deps.storage, auction_id, |state| -> StdResult<_> {
+        let mut state = state.ok_or_else(|| StdError::not_found("State"))?;
+        state.current_status = Status::Closed;
+        Ok(state)
+    })?;
+
+    Ok(resp)
+}
+
+/// Pays `amount` of the auction currency out to `recipient`
+fn send_tokens(denom: &Denom, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    let msg = match denom {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), denom),
+        }
+        .into(),
+        Denom::Cw20(address) => WasmMsg::Execute {
+            contract_addr: address.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    };
+
+    Ok(msg)
+}
+
+/// Hands an escrowed lot over to `recipient`; nothing to do when no token was escrowed
+fn release_lot(
+    storage: &mut dyn Storage,
+    auction_id: u64,
+    recipient: &Addr,
+) -> StdResult<Option<WasmMsg>> {
+    let mut lot = match LOTS.may_load(storage, auction_id)? {
+        Some(lot) if lot.escrowed => lot,
+        _ => return Ok(None),
+    };
+
+    lot.escrowed = false;
+    LOTS.save(storage, auction_id, &lot)?;
+
+    let transfer = Cw721ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: lot.token_id,
+    };
+
+    Ok(Some(WasmMsg::Execute {
+        contract_addr: lot.collection.into_string(),
+        msg: to_json_binary(&transfer)?,
+        funds: vec![],
+    }))
+}
+
+pub fn receive_nft(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let Cw721HookMsg::Escrow { auction_id } = from_json(&msg.msg)?;
+
+    let owner = CONFIGS.load(deps.storage, auction_id)?.owner;
+    let status = STATES.load(deps.storage, auction_id)?.current_status;
+
+    if status != Status::Open {
+        return Err(ContractError::BiddingClosed);
+    }
+
+    if owner != msg.sender {
+        return Err(ContractError::Unauthorized {
+            owner: owner.to_string(),
+        });
+    }
+
+    let mut lot = match LOTS.may_load(deps.storage, auction_id)? {
+        Some(lot) => lot,
+        None => return Err(ContractError::InvalidLot),
+    };
+
+    if lot.escrowed || lot.collection != info.sender || lot.token_id != msg.token_id {
+        return Err(ContractError::InvalidLot);
+    }
+
+    lot.escrowed = true;
+    LOTS.save(deps.storage, auction_id, &lot)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "escrow_lot")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("collection", info.sender.as_str())
+        .add_attribute("token_id", msg.token_id))
+}
+
+pub fn set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    operation: Operation,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let pauser = PAUSER.may_load(deps.storage)?;
+
+    if pauser.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {
+            owner: pauser.map(Addr::into_string).unwrap_or_default(),
+        });
+    }
+
+    let mut state = PAUSED.may_load(deps.storage)?.unwrap_or_default();
+    match operation {
+        Operation::Bid => state.bid = paused,
+        Operation::Close => state.close = paused,
+        Operation::Retract => state.retract = paused,
+    }
+    PAUSED.save(deps.storage, &state)?;
+
+    let action = if paused { "pause" } else { "unpause" };
+
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("operation", operation.to_string())
+        .add_attribute("sender", info.sender.as_str()))
+}
+
+fn ensure_not_paused(storage: &dyn Storage, operation: Operation) -> Result<(), ContractError> {
+    let state = PAUSED.may_load(storage)?.unwrap_or_default();
+
+    let paused = match operation {
+        Operation::Bid => state.bid,
+        Operation::Close => state.close,
+        Operation::Retract => state.retract,
+    };
+
+    if paused {
+        return Err(ContractError::Paused { operation });
+    }
+
+    Ok(())
+}
+
+pub fn cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    let state = STATES.load(deps.storage, auction_id)?;
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    ensure_owner(deps.as_ref(), auction_id, &config, &info.sender)?;
+
+    if state.current_status != Status::Open || state.end_time.is_expired(&env.block) {
+        return Err(ContractError::BiddingClosed);
+    }
+
+    let allowed = match config.cancellation {
+        Cancellation::Anytime => true,
+        Cancellation::BeforeFirstBid => bids()
+            .prefix(auction_id)
+            .keys_raw(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_none(),
+        Cancellation::BeforeEnd(window) => match (state.end_time, window) {
+            (Expiration::AtTime(end), Duration::Time(window)) => {
+                env.block.time.plus_seconds(window) < end
+            }
+            (Expiration::AtHeight(end), Duration::Height(window)) => {
+                env.block.height + window < end
+            }
+            _ => true,
+        },
+    };
+
+    if !allowed {
+        return Err(ContractError::CancelNotAllowed);
+    }
+
+    let resp = cancel_auction(deps.storage, auction_id)?
+        .add_attribute("action", "cancel")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", info.sender.as_str());
+
+    Ok(resp)
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+    denom: Option<Denom>,
+    commission: Option<Decimal>,
+    fee_mode: Option<FeeMode>,
+    fee_recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = STATES.load(deps.storage, auction_id)?;
+    let mut config = CONFIGS.load(deps.storage, auction_id)?;
+
+    ensure_owner(deps.as_ref(), auction_id, &config, &info.sender)?;
+
+    if state.current_status != Status::Open {
+        return Err(ContractError::BiddingClosed);
+    }
+
+    // Bidders committed funds under the terms they saw
+    let has_bids = bids()
+        .prefix(auction_id)
+        .keys_raw(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    let locks_terms = denom.is_some() || commission.is_some() || fee_mode.is_some();
+
+    if locks_terms && has_bids {
+        return Err(ContractError::ConfigLocked);
+    }
+
+    if let Some(denom) = denom {
+        if let Denom::Cw20(address) = &denom {
+            deps.api.addr_validate(address.as_str())?;
+        }
+
+        validate_accepted_denoms(&denom, &config.accepted_denoms)?;
+        config.denom = denom;
+    }
+
+    if let Some(commission) = commission {
+        if commission > fee_cap(deps.storage)? {
+            return Err(ContractError::InvalidCommission);
+        }
+
+        config.commission = commission;
+    }
+
+    if let Some(fee_mode) = fee_mode {
+        if fee_mode == FeeMode::PerBid && !config.keeper_reward.is_zero() {
+            return Err(ContractError::InvalidKeeperReward);
+        }
+
+        if fee_mode == FeeMode::PerBid && matches!(config.kind, AuctionKind::Sealed { .. }) {
+            return Err(ContractError::InvalidFeeMode);
+        }
+
+        config.fee_mode = fee_mode;
+    }
+
+    if let Some(fee_recipient) = fee_recipient {
+        config.fee_recipient = Some(deps.api.addr_validate(&fee_recipient)?);
+    }
+
+    CONFIGS.save(deps.storage, auction_id, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", info.sender.as_str()))
+}
+
+pub fn propose_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    ensure_owner(deps.as_ref(), auction_id, &config, &info.sender)?;
+
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+
+    let mut ownership = OWNERSHIPS
+        .may_load(deps.storage, auction_id)?
+        .unwrap_or_default();
+    ownership.pending_owner = Some(new_owner.clone());
+    OWNERSHIPS.save(deps.storage, auction_id, &ownership)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_owner")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("pending_owner", new_owner.as_str()))
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIGS.load(deps.storage, auction_id)?;
+    let mut ownership = OWNERSHIPS
+        .may_load(deps.storage, auction_id)?
+        .unwrap_or_default();
+
+    let pending_owner = match ownership.pending_owner.take() {
+        Some(pending_owner) => pending_owner,
+        None => return Err(ContractError::NoPendingOwner),
+    };
+
+    if pending_owner != info.sender {
+        return Err(ContractError::Unauthorized {
+            owner: pending_owner.to_string(),
+        });
+    }
+
+    // The new owner would otherwise be bidding on their own auction
+    if bids().has(deps.storage, (auction_id, &info.sender)) {
+        return Err(ContractError::UnauthorizedBid {
+            owner: info.sender.to_string(),
+        });
+    }
+
+    config.owner = pending_owner;
+    CONFIGS.save(deps.storage, auction_id, &config)?;
+    OWNERSHIPS.save(deps.storage, auction_id, &ownership)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("owner", info.sender.as_str()))
+}
+
+pub fn renounce_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    ensure_owner(deps.as_ref(), auction_id, &config, &info.sender)?;
+
+    // Without an owner to close it, the auction has to be able to run out
+    if let Expiration::Never {} = config.end_time {
+        return Err(ContractError::CannotRenounce);
+    }
+
+    OWNERSHIPS.save(
+        deps.storage,
+        auction_id,
+        &Ownership {
+            pending_owner: None,
+            renounced: true,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "renounce_ownership")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", info.sender.as_str()))
+}
+
+pub fn update_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+    root: Option<HexBinary>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIGS.load(deps.storage, auction_id)?;
+
+    ensure_owner(deps.as_ref(), auction_id, &config, &info.sender)?;
+
+    if root.as_ref().is_some_and(|root| root.len() != 32) {
+        return Err(ContractError::InvalidAllowlist);
+    }
+
+    let resp = Response::new()
+        .add_attribute("action", "update_allowlist")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", info.sender.as_str());
+    let resp = match &root {
+        Some(root) => resp.add_attribute("allowlist", root.to_hex()),
+        None => resp,
+    };
+
+    config.allowlist = root;
+    CONFIGS.save(deps.storage, auction_id, &config)?;
+
+    Ok(resp)
+}
+
+pub fn set_denied(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+    addresses: Vec<String>,
+    denied: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    ensure_owner(deps.as_ref(), auction_id, &config, &info.sender)?;
+
+    // A denied leader would still be sold the lot, so they have to be outbid first
+    let leader = STATES
+        .load(deps.storage, auction_id)?
+        .highest_bid
+        .map(|bid| bid.bidder);
+
+    let action = if denied { "deny" } else { "allow" };
+    let mut resp = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("sender", info.sender.as_str());
+
+    for address in addresses {
+        let address = deps.api.addr_validate(&address)?;
+
+        if denied {
+            if leader.as_ref() == Some(&address) {
+                return Err(ContractError::DenyLeader {
+                    address: address.to_string(),
+                });
+            }
+
+            DENIED.save(deps.storage, (auction_id, &address), &Empty {})?;
+        } else {
+            DENIED.remove(deps.storage, (auction_id, &address));
+        }
+
+        resp = resp.add_attribute("address", address.as_str());
+    }
+
+    Ok(resp)
+}
+
+/// Only a current, not renounced, owner may manage the auction
+fn ensure_owner(
+    deps: Deps,
+    auction_id: u64,
+    config: &Config,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let renounced = OWNERSHIPS
+        .may_load(deps.storage, auction_id)?
+        .is_some_and(|ownership| ownership.renounced);
+
+    if renounced || config.owner != *sender {
+        return Err(ContractError::Unauthorized {
+            owner: config.owner.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+pub fn refund_losers(
+    deps: DepsMut,
+    auction_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::Retract)?;
+
+    let status = STATES.load(deps.storage, auction_id)?.current_status;
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    if status == Status::Open {
+        return Err(ContractError::BiddingActive);
+    }
+
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let bidders = bids()
+        .prefix(auction_id)
+        .keys(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut resp = Response::new();
+
+    for bidder in &bidders {
+        let refund = refund_bid(deps.storage, &config, auction_id, bidder, bidder)?;
+
+        resp = resp
+            .add_submessages(refund.messages)
+            .add_attributes(refund.attributes)
+            .add_attribute("refunded", bidder.as_str());
+    }
+
+    Ok(resp
+        .add_attribute("action", "refund_losers")
+        .add_attribute("auction_id", auction_id.to_string())
+        .add_attribute("count", bidders.len().to_string()))
+}
+
+/// Appends an accepted bid to the auction's history
+fn record_bid(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    auction_id: u64,
+    bidder: &Addr,
+    increment: Uint128,
+    total: Uint128,
+) -> StdResult<()> {
+    let history = bid_history();
+
+    let last = history
+        .prefix(auction_id)
+        .keys(storage, None, None, Order::Descending)
+        .next()
+        .transpose()?;
+    let seq = last.map_or(1, |seq| seq + 1);
+
+    history.save(
+        storage,
+        (auction_id, seq),
+        &BidRecord {
+            auction_id,
+            seq,
+            bidder: bidder.clone(),
+            increment,
+            total,
+            height: block.height,
+            time: block.time,
+        },
+    )
+}
+
+fn fee_cap(storage: &dyn Storage) -> StdResult<Decimal> {
+    Ok(FEE_CAP.may_load(storage)?.unwrap_or(Decimal::one()))
+}
+
+/// Commission actually charged, lowered to the fee cap when governance set one below it
+fn fee_rate(storage: &dyn Storage, config: &Config) -> StdResult<Decimal> {
+    Ok(config.commission.min(fee_cap(storage)?))
+}
+
+/// Withdraws an open auction and hands the lot back to the owner
+fn cancel_auction(storage: &mut dyn Storage, auction_id: u64) -> Result<Response, ContractError> {
+    let mut state = STATES.load(storage, auction_id)?;
+    let owner = CONFIGS.load(storage, auction_id)?.owner;
+
+    if state.current_status != Status::Open {
+        return Err(ContractError::BiddingClosed);
+    }
+
+    state.current_status = Status::Cancelled;
+    STATES.save(storage, auction_id, &state)?;
+
+    let lot_msg = release_lot(storage, auction_id, &owner)?;
+
+    Ok(Response::new().add_messages(lot_msg))
+}
+
+/// Pays out everything `bidder` has left in escrow and clears the entry
+fn refund_bid(
+    storage: &mut dyn Storage,
+    config: &Config,
+    auction_id: u64,
+    bidder: &Addr,
+    receiver: &Addr,
+) -> Result<Response, ContractError> {
+    let amount = bids().load(storage, (auction_id, bidder))?;
+    let cancelled = STATES.load(storage, auction_id)?.current_status == Status::Cancelled;
+    let mut penalty = Uint128::zero();
+    let mut resp = Response::new();
+
+    // Sealed bids that were never validly revealed lose part of their deposit to the owner,
+    // unless the auction was called off or the bidder was denied and so kept from revealing
+    if let AuctionKind::Sealed { forfeit, .. } = config.kind {
+        let commitment = COMMITMENTS.may_load(storage, (auction_id, bidder))?;
+        let denied = DENIED.has(storage, (auction_id, bidder));
+
+        if !cancelled && !denied && matches!(commitment, Some(commitment) if !commitment.revealed) {
+            penalty = amount.checked_mul_floor(forfeit)?;
+        }
+    }
+
+    if !penalty.is_zero() {
+        for (denom, amount) in withdraw_escrow(storage, config, auction_id, bidder, penalty)? {
+            resp = resp.add_message(send_tokens(&denom, &config.owner, amount)?);
+        }
+
+        resp = resp.add_attribute("forfeited", penalty);
+    }
+
+    let refund = amount.checked_sub(penalty)?;
+    for (denom, amount) in withdraw_escrow(storage, config, auction_id, bidder, refund)? {
+        resp = resp.add_message(send_tokens(&denom, receiver, amount)?);
+    }
+
+    Ok(resp)
+}
+
+pub fn retract(
+    deps: DepsMut,
+    info: MessageInfo,
+    auction_id: u64,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage, Operation::Retract)?;
+
+    let status = STATES.load(deps.storage, auction_id)?.current_status;
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    if status == Status::Open {
+        return Err(ContractError::BiddingActive);
+    }
+
+    if !bids().has(deps.storage, (auction_id, &info.sender)) {
+        return Err(ContractError::InvalidRetract);
+    }
+
+    let receiver = match receiver {
+        Some(receiver) => deps.api.addr_validate(&receiver)?,
+        None => info.sender.clone(),
+    };
+
+    let resp = refund_bid(deps.storage, &config, auction_id, &info.sender, &receiver)?;
+
+    Ok(resp.add_attributes(vec![
+        ("action", "retract"),
+        ("auction_id", &auction_id.to_string()),
+        ("sender", info.sender.as_str()),
+        ("retract_funds_to_receiver", receiver.as_str()),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    use QueryMsg::*;
+
+    match msg {
+        TotalBids {
+            auction_id,
+            address,
+        } => to_json_binary(&self::total_bids(deps, auction_id, address)?),
+        HighestBid { auction_id } => to_json_binary(&self::highest_bid(deps, auction_id)?),
+        BiddingCompleted { auction_id } => {
+            to_json_binary(&self::bidding_completed(deps, env, auction_id)?)
+        }
+        WinningBid { auction_id } => to_json_binary(&self::winning_bid(deps, env, auction_id)?),
+        EndTime { auction_id } => to_json_binary(&self::end_time(deps, auction_id)?),
+        Lot { auction_id } => to_json_binary(&self::lot(deps, auction_id)?),
+        CurrentPrice { auction_id } => {
+            to_json_binary(&self::current_price_query(deps, env, auction_id)?)
+        }
+        UnrefundedBids { auction_id } => to_json_binary(&self::unrefunded_bids(deps, auction_id)?),
+        Ownership { auction_id } => to_json_binary(&self::ownership(deps, auction_id)?),
+        PauseState {} => to_json_binary(&self::pause_state(deps)?),
+        IsEligible {
+            auction_id,
+            address,
+            proof,
+        } => to_json_binary(&self::is_eligible(deps, auction_id, address, proof)?),
+        DeniedAddresses {
+            auction_id,
+            start_after,
+            limit,
+        } => to_json_binary(&self::denied_addresses(
+            deps,
+            auction_id,
+            start_after,
+            limit,
+        )?),
+        FeeSchedule { auction_id } => to_json_binary(&self::fee_schedule(deps, auction_id)?),
+        AllBids {
+            auction_id,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&self::all_bids(
+            deps,
+            auction_id,
+            start_after,
+            limit,
+            order,
+        )?),
+        TopBids { auction_id, n } => to_json_binary(&self::all_bids(
+            deps,
+            auction_id,
+            None,
+            Some(n),
+            Some(OrderBy::Descending),
+        )?),
+        BidHistory {
+            auction_id,
+            start_after,
+            limit,
+            bidder,
+        } => to_json_binary(&self::bid_history_query(
+            deps,
+            auction_id,
+            start_after,
+            limit,
+            bidder,
+        )?),
+        ListAuctions {
+            status,
+            owner,
+            start_after,
+            limit,
+        } => to_json_binary(&self::list_auctions(
+            deps,
+            status,
+            owner,
+            start_after,
+            limit,
+        )?),
+    }
+}
+
+pub fn total_bids(deps: Deps, auction_id: u64, address: String) -> StdResult<Uint128> {
+    let address = deps.api.addr_validate(&address)?;
+
+    Ok(bids()
+        .may_load(deps.storage, (auction_id, &address))?
+        .unwrap_or_default())
+}
+
+pub fn highest_bid(deps: Deps, auction_id: u64) -> StdResult<BidResponse> {
+    match STATES.load(deps.storage, auction_id)?.highest_bid {
+        Some(StandingBid { bidder, amount }) => Ok(BidResponse {
+            address: bidder,
+            amount,
+        }),
         None => Err(StdError::not_found("Auction has no bid")),
     }
 }
+
+pub fn bidding_completed(deps: Deps, env: Env, auction_id: u64) -> StdResult<bool> {
+    is_completed(deps, auction_id, &env.block)
+}
+
+pub fn winning_bid(deps: Deps, env: Env, auction_id: u64) -> StdResult<BidResponse> {
+    if !is_completed(deps, auction_id, &env.block)? {
+        return Err(StdError::generic_err("Auction is still open"));
+    }
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+    let state = STATES.load(deps.storage, auction_id)?;
+    if state.current_status == Status::Cancelled {
+        return Err(StdError::generic_err("Auction cancelled"));
+    }
+    // The reserve is only enforced on settlement, but the outcome is known once bidding ends
+    let highest_bid_amount = state.highest_bid.as_ref().map(|bid| bid.amount);
+    let reserve_missed = config
+        .reserve_price
+        .is_some_and(|reserve_price| highest_bid_amount.unwrap_or_default() < reserve_price);
+    if state.current_status == Status::Failed || reserve_missed {
+        return Err(StdError::generic_err(
+            "Auction failed: reserve price not met",
+        ));
+    }
+    match state.highest_bid {
+        Some(StandingBid { bidder, amount }) => Ok(BidResponse {
+            address: bidder,
+            amount,
+        }),
+        None => Err(StdError::not_found("Auction has no bid")),
+    }
+}
+
+pub fn end_time(deps: Deps, auction_id: u64) -> StdResult<Expiration> {
+    Ok(STATES.load(deps.storage, auction_id)?.end_time)
+}
+
+pub fn current_price_query(deps: Deps, env: Env, auction_id: u64) -> StdResult<Uint128> {
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+    let state = STATES.load(deps.storage, auction_id)?;
+
+    current_price(&config, &state, &env.block)
+}
+
+pub fn unrefunded_bids(deps: Deps, auction_id: u64) -> StdResult<u64> {
+    let count = bids()
+        .prefix(auction_id)
+        .keys_raw(deps.storage, None, None, Order::Ascending)
+        .count();
+
+    Ok(count as u64)
+}
+
+pub fn ownership(deps: Deps, auction_id: u64) -> StdResult<OwnershipResponse> {
+    let owner = CONFIGS.load(deps.storage, auction_id)?.owner;
+    let ownership = OWNERSHIPS
+        .may_load(deps.storage, auction_id)?
+        .unwrap_or_default();
+
+    Ok(OwnershipResponse {
+        owner,
+        pending_owner: ownership.pending_owner,
+        renounced: ownership.renounced,
+    })
+}
+
+pub fn pause_state(deps: Deps) -> StdResult<PauseStateResponse> {
+    Ok(PauseStateResponse {
+        pauser: PAUSER.may_load(deps.storage)?,
+        paused: PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn is_eligible(
+    deps: Deps,
+    auction_id: u64,
+    address: String,
+    proof: Option<Vec<HexBinary>>,
+) -> StdResult<bool> {
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+    let address = deps.api.addr_validate(&address)?;
+
+    let eligible = config.owner != address
+        && ensure_not_denied(deps.storage, auction_id, &address).is_ok()
+        && ensure_allowlisted(&config.allowlist, &address, proof).is_ok();
+
+    Ok(eligible)
+}
+
+pub fn denied_addresses(
+    deps: Deps,
+    auction_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<DeniedAddressesResponse> {
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let addresses = DENIED
+        .prefix(auction_id)
+        .keys(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    Ok(DeniedAddressesResponse { addresses })
+}
+
+pub fn fee_schedule(deps: Deps, auction_id: u64) -> StdResult<FeeScheduleResponse> {
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+
+    Ok(FeeScheduleResponse {
+        commission: fee_rate(deps.storage, &config)?,
+        fee_mode: config.fee_mode,
+        fee_recipient: config.fee_recipient.unwrap_or(config.owner),
+    })
+}
+
+pub fn lot(deps: Deps, auction_id: u64) -> StdResult<Lot> {
+    LOTS.load(deps.storage, auction_id)
+}
+
+pub fn all_bids(
+    deps: Deps,
+    auction_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    order: Option<OrderBy>,
+) -> StdResult<AllBidsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let order = order.unwrap_or(OrderBy::Descending).into();
+
+    // The index orders by amount, then by bidder for equal amounts
+    let start = start_after
+        .map(|bidder| -> StdResult<_> {
+            let bidder = deps.api.addr_validate(&bidder)?;
+            let amount = bids().load(deps.storage, (auction_id, &bidder))?;
+
+            Ok(Bound::exclusive((amount.u128(), (auction_id, bidder))))
+        })
+        .transpose()?;
+
+    let (min, max) = match order {
+        Order::Ascending => (start, None),
+        Order::Descending => (None, start),
+    };
+
+    let bids = bids()
+        .idx
+        .amount
+        .sub_prefix(auction_id)
+        .range(deps.storage, min, max, order)
+        .take(limit)
+        .map(|item| {
+            let ((_, address), amount) = item?;
+            Ok(BidResponse { address, amount })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(AllBidsResponse { bids })
+}
+
+pub fn bid_history_query(
+    deps: Deps,
+    auction_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    bidder: Option<String>,
+) -> StdResult<BidHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let history = bid_history();
+
+    let bids = match bidder {
+        Some(bidder) => {
+            let bidder = deps.api.addr_validate(&bidder)?;
+            let start = start_after.map(|seq| Bound::exclusive((auction_id, seq)));
+
+            history
+                .idx
+                .bidder
+                .prefix((auction_id, bidder))
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .map(|item| item.map(|(_, record)| record))
+                .collect::<StdResult<_>>()?
+        }
+        None => {
+            let start = start_after.map(Bound::exclusive);
+
+            history
+                .prefix(auction_id)
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .map(|item| item.map(|(_, record)| record))
+                .collect::<StdResult<_>>()?
+        }
+    };
+
+    Ok(BidHistoryResponse { bids })
+}
+
+pub fn list_auctions(
+    deps: Deps,
+    status: Option<Status>,
+    owner: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListAuctionsResponse> {
+    let owner = owner
+        .map(|owner| deps.api.addr_validate(&owner))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let auctions = CONFIGS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match (item, &owner) {
+            (Ok((_, config)), Some(owner)) => config.owner == *owner,
+            _ => true,
+        })
+        .map(|item| {
+            let (auction_id, config) = item?;
+            let state = STATES.load(deps.storage, auction_id)?;
+
+            Ok(AuctionResponse {
+                auction_id,
+                config,
+                state,
+            })
+        })
+        .filter(|item| match (item, &status) {
+            (Ok(auction), Some(status)) => auction.state.current_status == *status,
+            _ => true,
+        })
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    Ok(ListAuctionsResponse { auctions })
+}
+
+/// An auction is over once it was closed or failed, or its end time has passed
+fn is_completed(deps: Deps, auction_id: u64, block: &BlockInfo) -> StdResult<bool> {
+    let config = CONFIGS.load(deps.storage, auction_id)?;
+    let state = STATES.load(deps.storage, auction_id)?;
+
+    Ok(state.current_status != Status::Open || bidding_end(&config, &state)?.is_expired(block))
+}
+
+/// Amount the winner pays, never more than the winning bid
+fn clearing_price(config: &Config, state: &State) -> Uint128 {
+    let highest_bid_amount = match &state.highest_bid {
+        Some(highest_bid) => highest_bid.amount,
+        None => Uint128::zero(),
+    };
+
+    match config.settlement {
+        Settlement::FirstPrice => highest_bid_amount,
+        Settlement::SecondPrice => {
+            let runner_up_amount = match &state.runner_up {
+                Some(runner_up) => runner_up.amount,
+                None => Uint128::zero(),
+            };
+
+            runner_up_amount
+                .max(config.reserve_price.unwrap_or_default())
+                .max(config.opening_price)
+                .min(highest_bid_amount)
+        }
+    }
+}
+
+/// Point after which no more bids can come in. Sealed auctions keep going through
+/// the reveal phase that follows their end time.
+fn bidding_end(config: &Config, state: &State) -> StdResult<Expiration> {
+    match config.kind {
+        AuctionKind::Sealed { reveal_period, .. } => state.end_time + reveal_period,
+        _ => Ok(state.end_time),
+    }
+}
+
+/// Lowest total bid the auction accepts right now. For english auctions this is the
+/// opening price or the leader plus the minimum increment, for dutch auctions the
+/// price on the descending curve.
+fn current_price(config: &Config, state: &State, block: &BlockInfo) -> StdResult<Uint128> {
+    let price = match &config.kind {
+        AuctionKind::English => {
+            let price = match &state.highest_bid {
+                Some(StandingBid {
+                    amount: highest_bid_amount,
+                    ..
+                }) => {
+                    let highest_bid_amount = *highest_bid_amount;
+                    let increment = match config.min_increment {
+                        Some(BidIncrement::Amount(amount)) => amount,
+                        Some(BidIncrement::Percent(percent)) => highest_bid_amount
+                            .checked_mul_floor(percent)
+                            .map_err(|err| StdError::generic_err(err.to_string()))?,
+                        None => Uint128::zero(),
+                    };
+                    highest_bid_amount.checked_add(increment.max(Uint128::one()))?
+                }
+                None => config.opening_price.max(Uint128::one()),
+            };
+
+            // Reaching the buy-now price always wins, even when it is less than a full increment
+            match config.buy_now {
+                Some(buy_now) => price.min(buy_now),
+                None => price,
+            }
+        }
+        AuctionKind::Sealed { .. } => config.opening_price.max(Uint128::one()),
+        AuctionKind::Dutch {
+            start_price,
+            floor_price,
+            step,
+        } => {
+            let (start, end) = match (config.start_time, config.end_time) {
+                (Some(Scheduled::AtTime(start)), Expiration::AtTime(end)) => (start, end),
+                _ => return Ok(*floor_price),
+            };
+
+            let duration = end.seconds() - start.seconds();
+            let mut elapsed = block.time.seconds().saturating_sub(start.seconds());
+            if elapsed >= duration {
+                return Ok(*floor_price);
+            }
+
+            if let Some(step) = step {
+                elapsed -= elapsed % step;
+            }
+
+            let drop = (start_price - floor_price).multiply_ratio(elapsed, duration);
+            start_price - drop
+        }
+    };
+
+    Ok(price)
+}
+
+/// Pushes the end time out so that at least `window` remains after a bid
+fn extend_end_time(end_time: Expiration, window: Duration, block: &BlockInfo) -> Expiration {
+    match (end_time, window) {
+        (Expiration::AtTime(end), Duration::Time(secs)) => {
+            Expiration::AtTime(end.max(block.time.plus_seconds(secs)))
+        }
+        (Expiration::AtHeight(end), Duration::Height(blocks)) => {
+            Expiration::AtHeight(end.max(block.height + blocks))
+        }
+        _ => end_time,
+    }
+}
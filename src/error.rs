@@ -1,4 +1,6 @@
-use cosmwasm_std::{StdError, Uint128};
+use cosmwasm_std::{CheckedMultiplyFractionError, OverflowError, StdError, Uint128};
+
+use crate::state::Operation;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,32 +8,117 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("{0}")]
+    CheckedMultiplyFraction(#[from] CheckedMultiplyFractionError),
+
+    #[error("Cannot migrate from {found}, expected {expected}")]
+    WrongContract { expected: String, found: String },
+
+    #[error("Cannot downgrade from {stored} to {current}")]
+    CannotDowngrade { stored: String, current: String },
+
     #[error("Unauthorized")]
     Unauthorized { owner: String },
 
+    #[error("Operation paused: {operation}")]
+    Paused { operation: Operation },
+
     #[error("Unauthorized bid")]
     UnauthorizedBid { owner: String },
 
+    #[error("No ownership transfer pending")]
+    NoPendingOwner,
+
+    #[error("Cannot renounce ownership of an auction without an end time")]
+    CannotRenounce,
+
+    #[error("Auction can no longer be cancelled")]
+    CancelNotAllowed,
+
+    #[error("Config can no longer be changed once bidding has started")]
+    ConfigLocked,
+
     #[error("Invalid retract")]
     InvalidRetract,
 
     #[error("Bidding closed")]
     BiddingClosed,
 
+    #[error("Bidding not started")]
+    BiddingNotStarted,
+
+    #[error("Invalid schedule")]
+    InvalidSchedule,
+
     #[error("Bidding is active")]
     BiddingActive,
 
     #[error("Invalid commission")]
-    InvalidCommission { funds: Uint128, commission: Uint128 },
+    InvalidCommission,
+
+    #[error("Invalid fee mode")]
+    InvalidFeeMode,
+
+    #[error("Invalid keeper reward")]
+    InvalidKeeperReward,
+
+    #[error("Invalid bid increment")]
+    InvalidIncrement,
+
+    #[error("Invalid buy now price")]
+    InvalidBuyNow,
+
+    #[error("Invalid dutch auction")]
+    InvalidDutchAuction,
+
+    #[error("Invalid forfeit")]
+    InvalidForfeit,
+
+    #[error("Operation not supported by this kind of auction")]
+    WrongAuctionKind,
+
+    #[error("Address denied: {address}")]
+    Denied { address: String },
+
+    #[error("Cannot deny the standing leader: {address}")]
+    DenyLeader { address: String },
+
+    #[error("Address is not on the allowlist")]
+    NotAllowlisted,
+
+    #[error("Invalid allowlist root")]
+    InvalidAllowlist,
+
+    #[error("Invalid commitment")]
+    InvalidCommitment,
+
+    #[error("Revealed bid does not match the commitment")]
+    InvalidReveal,
+
+    #[error("Deposit of {deposit} does not cover the revealed bid of {amount}")]
+    InsufficientDeposit { deposit: Uint128, amount: Uint128 },
+
+    #[error("Invalid lot")]
+    InvalidLot,
+
+    #[error("Lot not escrowed")]
+    LotNotEscrowed,
+
+    #[error("Invalid accepted denoms")]
+    InvalidDenoms,
 
     #[error("Invalid funds")]
     InvalidFunds,
 
-    #[error("Invalid bid")]
+    #[error("Invalid bid, minimum acceptable bid is {min_bid}")]
     InvalidBid {
         existing: Uint128,
         funds: Uint128,
         new_bid: Uint128,
         max_bid: Uint128,
+        min_bid: Uint128,
     },
 }
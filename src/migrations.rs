@@ -0,0 +1,129 @@
+//! Storage upgrades run by `contract::migrate`, one step per release that changed the layout
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Decimal, Order, StdResult, Storage, Uint128};
+use cw20::Denom;
+use cw_storage_plus::{Item, Map};
+use cw_utils::Expiration;
+
+use crate::state::{
+    bids, AuctionKind, Cancellation, Config, FeeMode, Settlement, StandingBid, State, Status,
+    AUCTION_COUNT, CONFIGS, STATES,
+};
+
+/// Single auction config of 0.1.0
+#[cw_serde]
+pub struct LegacyConfig {
+    pub owner: Addr,
+    pub denom: String,
+    pub commission: u128,
+}
+
+/// Single auction state of 0.1.0
+#[cw_serde]
+pub struct LegacyState {
+    pub current_status: Status,
+    pub highest_bid: Option<(Addr, Uint128)>,
+}
+
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("state");
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+pub const LEGACY_BIDS: Map<&Addr, Uint128> = Map::new("bids");
+
+/// Auction state with the leading bids still stored as tuples
+#[cw_serde]
+struct TupleState {
+    current_status: Status,
+    highest_bid: Option<(Addr, Uint128)>,
+    runner_up: Option<(Addr, Uint128)>,
+    end_time: Expiration,
+}
+
+const TUPLE_STATES: Map<u64, TupleState> = Map::new("auction_states");
+
+/// 0.1.0 -> 0.2.0: leading bids become `StandingBid`s, and the single auction of
+/// the original release becomes auction 1
+pub fn migrate_to_0_2_0(storage: &mut dyn Storage) -> StdResult<()> {
+    let states = TUPLE_STATES
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (auction_id, state) in states {
+        let state = State {
+            current_status: state.current_status,
+            highest_bid: state.highest_bid.map(standing_bid),
+            runner_up: state.runner_up.map(standing_bid),
+            end_time: state.end_time,
+        };
+
+        STATES.save(storage, auction_id, &state)?;
+    }
+
+    if let Some(state) = LEGACY_STATE.may_load(storage)? {
+        migrate_singleton(storage, state)?;
+    }
+
+    Ok(())
+}
+
+fn standing_bid((bidder, amount): (Addr, Uint128)) -> StandingBid {
+    StandingBid { bidder, amount }
+}
+
+fn migrate_singleton(storage: &mut dyn Storage, state: LegacyState) -> StdResult<()> {
+    const AUCTION_ID: u64 = 1;
+
+    let config = LEGACY_CONFIG.load(storage)?;
+
+    STATES.save(
+        storage,
+        AUCTION_ID,
+        &State {
+            current_status: state.current_status,
+            highest_bid: state.highest_bid.map(standing_bid),
+            runner_up: None,
+            end_time: Expiration::Never {},
+        },
+    )?;
+
+    // The original commission was never actually charged
+    CONFIGS.save(
+        storage,
+        AUCTION_ID,
+        &Config {
+            owner: config.owner,
+            kind: AuctionKind::English,
+            settlement: Settlement::FirstPrice,
+            denom: Denom::Native(config.denom),
+            commission: Decimal::zero(),
+            fee_mode: FeeMode::PerBid,
+            fee_recipient: None,
+            reserve_price: None,
+            opening_price: Uint128::zero(),
+            min_increment: None,
+            buy_now: None,
+            start_time: None,
+            end_time: Expiration::Never {},
+            extension_window: None,
+            keeper_reward: Decimal::zero(),
+            cancellation: Cancellation::Anytime,
+            allowlist: None,
+            accepted_denoms: vec![],
+        },
+    )?;
+
+    let legacy_bids = LEGACY_BIDS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (bidder, amount) in legacy_bids {
+        bids().save(storage, (AUCTION_ID, &bidder), &amount)?;
+        LEGACY_BIDS.remove(storage, &bidder);
+    }
+
+    AUCTION_COUNT.save(storage, &AUCTION_ID)?;
+    LEGACY_STATE.remove(storage);
+    LEGACY_CONFIG.remove(storage);
+
+    Ok(())
+}
@@ -1,31 +1,273 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Decimal, HexBinary, Order, Uint128};
+use cw20::{Cw20ReceiveMsg, Denom};
+use cw721::Cw721ReceiveMsg;
+use cw_utils::{Duration, Expiration, Scheduled};
+
+use crate::state::{
+    AuctionKind, BidIncrement, BidRecord, Cancellation, Config, DenomRate, FeeMode, Lot, Operation,
+    PauseState, Settlement, State, Status,
+};
 
 #[cw_serde]
+#[derive(Default)]
 pub struct InstantiateMsg {
+    /// Can pause bidding, closing and retracting; nothing can be paused when unset
+    pub pauser: Option<String>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+/// Overrides available to chain governance
+#[cw_serde]
+pub enum SudoMsg {
+    /// Ends the auction right away as if the owner closed it; sealed auctions still have to
+    /// get through their reveal period first
+    ForceClose {
+        auction_id: u64,
+    },
+    /// Withdraws the auction and returns the lot to the owner; bidders then get their bids back
+    /// in full through `Retract` or `RefundLosers`
+    Cancel {
+        auction_id: u64,
+    },
+    /// Caps the commission of every auction, existing ones included
+    SetFeeCap {
+        fee_cap: Decimal,
+    },
+    ReassignOwner {
+        auction_id: u64,
+        new_owner: String,
+    },
+    /// Replaces the pauser, or removes it and lifts every pause when unset
+    SetPauser {
+        pauser: Option<String>,
+    },
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct AuctionMsg {
     pub owner: Option<String>,
-    pub denom: String,
-    pub commission: Option<u128>,
+    /// English when unset
+    pub kind: Option<AuctionKind>,
+    /// First price when unset
+    pub settlement: Option<Settlement>,
+    /// Currency bids are placed in, either a native denom or a CW20 contract
+    pub denom: Denom,
+    /// Fee rate, at most 100%
+    pub commission: Option<Decimal>,
+    /// Charged on every bid when unset, on settlement for sealed auctions
+    pub fee_mode: Option<FeeMode>,
+    /// Receives the fees instead of the owner
+    pub fee_recipient: Option<String>,
+    /// Minimum the winning bid must reach, otherwise the auction fails
+    pub reserve_price: Option<Uint128>,
+    /// Smallest amount accepted as the first bid
+    pub opening_price: Option<Uint128>,
+    /// Nonzero, at most 100% when a percentage
+    pub min_increment: Option<BidIncrement>,
+    /// A bid reaching this total wins outright and closes the auction
+    pub buy_now: Option<Uint128>,
+    /// NFT to auction; bidding opens once it is sent in with `Cw721HookMsg::Escrow`
+    pub lot: Option<LotMsg>,
+    /// Bids are rejected until this point; open immediately when unset
+    pub start_time: Option<Scheduled>,
+    /// Bidding is over once this point passes; runs until closed when unset
+    pub end_time: Option<Expiration>,
+    /// A bid landing within this window of the end moves the end out to `window` from now
+    pub extension_window: Option<Duration>,
+    /// Share of the settlement fee paid to whoever settles the auction after it ends,
+    /// only with `FeeMode::OnSettlement`
+    pub keeper_reward: Option<Decimal>,
+    /// Owner can cancel any time before the auction ends when unset
+    pub cancellation: Option<Cancellation>,
+    /// Merkle root of the addresses allowed to bid, see `ExecuteMsg::Bid`
+    pub allowlist: Option<HexBinary>,
+    /// Further native denoms bids can be placed in, ranked by their value in `denom`
+    pub accepted_denoms: Option<Vec<DenomRate>>,
+}
+
+#[cw_serde]
+pub struct LotMsg {
+    pub collection: String,
+    pub token_id: String,
 }
 
 #[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
-    Bid {},
-    Close {},
-    Retract { receiver: Option<String> },
+    CreateAuction(AuctionMsg),
+    Receive(Cw20ReceiveMsg),
+    ReceiveNft(Cw721ReceiveMsg),
+    /// `proof` holds the sibling hashes leading from `sha256(sender)` up to the allowlist root
+    Bid {
+        auction_id: u64,
+        proof: Option<Vec<HexBinary>>,
+    },
+    /// Sealed auctions only; the attached funds are the deposit backing the hidden bid
+    Commit {
+        auction_id: u64,
+        commitment: HexBinary,
+        proof: Option<Vec<HexBinary>>,
+    },
+    Reveal {
+        auction_id: u64,
+        amount: Uint128,
+        salt: String,
+    },
+    Close {
+        auction_id: u64,
+    },
+    Settle {
+        auction_id: u64,
+    },
+    Retract {
+        auction_id: u64,
+        receiver: Option<String>,
+    },
+    /// Owner only; everything but the fee recipient is locked once the first bid comes in
+    UpdateConfig {
+        auction_id: u64,
+        denom: Option<Denom>,
+        commission: Option<Decimal>,
+        fee_mode: Option<FeeMode>,
+        fee_recipient: Option<String>,
+    },
+    /// Starts handing the auction, and its proceeds, over to `new_owner`
+    ProposeOwner {
+        auction_id: u64,
+        new_owner: String,
+    },
+    AcceptOwnership {
+        auction_id: u64,
+    },
+    /// Gives up closing and managing the auction; it can then only end through `Settle`
+    RenounceOwnership {
+        auction_id: u64,
+    },
+    /// Owner only; replaces the allowlist root, or opens bidding to anyone when unset
+    UpdateAllowlist {
+        auction_id: u64,
+        root: Option<HexBinary>,
+    },
+    /// Owner only; bars `addresses` from bidding, they can still retract what they already bid.
+    /// The standing leader cannot be denied
+    Deny {
+        auction_id: u64,
+        addresses: Vec<String>,
+    },
+    /// Owner only; lifts a `Deny`
+    Allow {
+        auction_id: u64,
+        addresses: Vec<String>,
+    },
+    /// Owner only; withdraws the auction without a sale so every bidder can retract in full
+    Cancel {
+        auction_id: u64,
+    },
+    /// Pauser only
+    Pause {
+        operation: Operation,
+    },
+    /// Pauser only
+    Unpause {
+        operation: Operation,
+    },
+    /// Sends every remaining bid back to its bidder once the auction is over, a page at a time
+    RefundLosers {
+        auction_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub enum Cw20HookMsg {
+    Bid {
+        auction_id: u64,
+        proof: Option<Vec<HexBinary>>,
+    },
+    Commit {
+        auction_id: u64,
+        commitment: HexBinary,
+        proof: Option<Vec<HexBinary>>,
+    },
+}
+
+#[cw_serde]
+pub enum Cw721HookMsg {
+    Escrow { auction_id: u64 },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     #[returns(Uint128)]
-    TotalBids { address: String },
+    TotalBids { auction_id: u64, address: String },
     #[returns(BidResponse)]
-    HighestBid {},
+    HighestBid { auction_id: u64 },
     #[returns(bool)]
-    BiddingCompleted {},
+    BiddingCompleted { auction_id: u64 },
     #[returns(BidResponse)]
-    WinningBid {},
+    WinningBid { auction_id: u64 },
+    #[returns(Expiration)]
+    EndTime { auction_id: u64 },
+    #[returns(Lot)]
+    Lot { auction_id: u64 },
+    /// Smallest total bid accepted right now, the live price for dutch auctions
+    #[returns(Uint128)]
+    CurrentPrice { auction_id: u64 },
+    /// Number of bidders that still have funds in escrow
+    #[returns(u64)]
+    UnrefundedBids { auction_id: u64 },
+    #[returns(OwnershipResponse)]
+    Ownership { auction_id: u64 },
+    #[returns(PauseStateResponse)]
+    PauseState {},
+    /// Whether `address` may bid on the auction with the given proof
+    #[returns(bool)]
+    IsEligible {
+        auction_id: u64,
+        address: String,
+        proof: Option<Vec<HexBinary>>,
+    },
+    #[returns(DeniedAddressesResponse)]
+    DeniedAddresses {
+        auction_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(FeeScheduleResponse)]
+    FeeSchedule { auction_id: u64 },
+    /// Bidders with the funds they hold in escrow, ranked by amount, highest first by default
+    #[returns(AllBidsResponse)]
+    AllBids {
+        auction_id: u64,
+        /// Bidder the previous page ended with
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<OrderBy>,
+    },
+    /// The `n` largest bids
+    #[returns(AllBidsResponse)]
+    TopBids { auction_id: u64, n: u32 },
+    /// Accepted bids oldest first, optionally only those from `bidder`
+    #[returns(BidHistoryResponse)]
+    BidHistory {
+        auction_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        bidder: Option<String>,
+    },
+    #[returns(ListAuctionsResponse)]
+    ListAuctions {
+        status: Option<Status>,
+        owner: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -33,3 +275,65 @@ pub struct BidResponse {
     pub address: Addr,
     pub amount: Uint128,
 }
+
+#[cw_serde]
+pub enum OrderBy {
+    Ascending,
+    Descending,
+}
+
+impl From<OrderBy> for Order {
+    fn from(order: OrderBy) -> Self {
+        match order {
+            OrderBy::Ascending => Order::Ascending,
+            OrderBy::Descending => Order::Descending,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct AllBidsResponse {
+    pub bids: Vec<BidResponse>,
+}
+
+#[cw_serde]
+pub struct BidHistoryResponse {
+    pub bids: Vec<BidRecord>,
+}
+
+#[cw_serde]
+pub struct OwnershipResponse {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+    pub renounced: bool,
+}
+
+#[cw_serde]
+pub struct PauseStateResponse {
+    pub pauser: Option<Addr>,
+    pub paused: PauseState,
+}
+
+#[cw_serde]
+pub struct DeniedAddressesResponse {
+    pub addresses: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct FeeScheduleResponse {
+    pub commission: Decimal,
+    pub fee_mode: FeeMode,
+    pub fee_recipient: Addr,
+}
+
+#[cw_serde]
+pub struct AuctionResponse {
+    pub auction_id: u64,
+    pub config: Config,
+    pub state: State,
+}
+
+#[cw_serde]
+pub struct ListAuctionsResponse {
+    pub auctions: Vec<AuctionResponse>,
+}
@@ -1,10 +1,18 @@
-use cosmwasm_std::{Addr, Coin, StdResult, Uint128};
+use cosmwasm_std::{Addr, Coin, Decimal, HexBinary, StdResult, Uint128};
+use cw20::Denom;
 use cw_multi_test::{App, ContractWrapper, Executor};
+use cw_utils::Expiration;
 
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query, sudo};
 use crate::error::ContractError;
-use crate::msg::{BidResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::msg::{
+    AllBidsResponse, AuctionMsg, BidHistoryResponse, BidResponse, DeniedAddressesResponse,
+    ExecuteMsg, FeeScheduleResponse, InstantiateMsg, ListAuctionsResponse, MigrateMsg, OrderBy,
+    OwnershipResponse, PauseStateResponse, QueryMsg, SudoMsg,
+};
+use crate::state::{FeeMode, Lot, Operation, Status};
 
+#[derive(Debug)]
 pub struct BiddingContract(Addr);
 
 impl BiddingContract {
@@ -13,51 +21,236 @@ impl BiddingContract {
     }
 
     pub fn store_code(app: &mut App) -> u64 {
-        let contract = ContractWrapper::new(execute, instantiate, query);
+        let contract = ContractWrapper::new(execute, instantiate, query)
+            .with_migrate(migrate)
+            .with_sudo(sudo);
         app.store_code(Box::new(contract))
     }
 
     #[track_caller]
-    pub fn instantiate<'a>(
+    pub fn instantiate(
         app: &mut App,
         code_id: u64,
         sender: &Addr,
         label: &str,
+    ) -> Result<Self, ContractError> {
+        Self::instantiate_with_msg(app, code_id, sender, label, &InstantiateMsg::default())
+    }
+
+    #[track_caller]
+    pub fn instantiate_with_msg(
+        app: &mut App,
+        code_id: u64,
+        sender: &Addr,
+        label: &str,
+        msg: &InstantiateMsg,
+    ) -> Result<Self, ContractError> {
+        app.instantiate_contract(code_id, sender.clone(), msg, &[], label, None)
+            .map(BiddingContract)
+            .map_err(|err| err.downcast().unwrap())
+    }
+
+    #[track_caller]
+    pub fn migrate(
+        app: &mut App,
+        code_id: u64,
+        sender: &Addr,
+        contract: &Addr,
+    ) -> Result<Self, ContractError> {
+        app.migrate_contract(sender.clone(), contract.clone(), &MigrateMsg {}, code_id)
+            .map(|_| BiddingContract(contract.clone()))
+            .map_err(|err| err.downcast().unwrap())
+    }
+
+    #[track_caller]
+    pub fn create_auction<'a>(
+        &self,
+        app: &mut App,
+        sender: &Addr,
         owner: impl Into<Option<&'a Addr>>,
         denom: &str,
-        commission: impl Into<Option<u128>>,
-    ) -> StdResult<Self> {
+        commission: impl Into<Option<Decimal>>,
+    ) -> Result<u64, ContractError> {
         let owner = owner.into();
         let commission = commission.into();
 
-        app.instantiate_contract(
-            code_id,
-            sender.clone(),
-            &InstantiateMsg {
-                denom: denom.to_string(),
+        self.create_auction_with_msg(
+            app,
+            sender,
+            &AuctionMsg {
+                denom: Denom::Native(denom.to_string()),
                 owner: owner.map(Addr::to_string),
                 commission,
+                ..Default::default()
             },
+        )
+    }
+
+    #[track_caller]
+    pub fn create_auction_with_msg(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        msg: &AuctionMsg,
+    ) -> Result<u64, ContractError> {
+        let resp = app
+            .execute_contract(
+                sender.clone(),
+                self.0.clone(),
+                &ExecuteMsg::CreateAuction(msg.clone()),
+                &[],
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+
+        let auction_id = resp
+            .events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "auction_id")
+            .map(|attr| attr.value.parse().unwrap())
+            .unwrap();
+
+        Ok(auction_id)
+    }
+
+    #[track_caller]
+    pub fn bid(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+        funds: &[Coin],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Bid {
+                auction_id,
+                proof: None,
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn bid_with_proof(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+        proof: Vec<HexBinary>,
+        funds: &[Coin],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Bid {
+                auction_id,
+                proof: Some(proof),
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn commit(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+        commitment: HexBinary,
+        funds: &[Coin],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Commit {
+                auction_id,
+                commitment,
+                proof: None,
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn reveal(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+        amount: u128,
+        salt: &str,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Reveal {
+                auction_id,
+                amount: Uint128::new(amount),
+                salt: salt.to_string(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn close(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Close { auction_id },
             &[],
-            label,
-            None,
         )
-        .map(BiddingContract)
         .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
     }
 
     #[track_caller]
-    pub fn bid(&self, app: &mut App, sender: &Addr, funds: &[Coin]) -> Result<(), ContractError> {
-        app.execute_contract(sender.clone(), self.0.clone(), &ExecuteMsg::Bid {}, funds)
-            .map_err(|err| err.downcast().unwrap())
-            .map(|_| ())
+    pub fn cancel(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Cancel { auction_id },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
     }
 
     #[track_caller]
-    pub fn close(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
-        app.execute_contract(sender.clone(), self.0.clone(), &ExecuteMsg::Close {}, &[])
-            .map_err(|err| err.downcast().unwrap())
-            .map(|_| ())
+    pub fn settle(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Settle { auction_id },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
     }
 
     #[track_caller]
@@ -65,6 +258,7 @@ impl BiddingContract {
         &self,
         app: &mut App,
         sender: &Addr,
+        auction_id: u64,
         receiver: impl Into<Option<&'a Addr>>,
     ) -> Result<(), ContractError> {
         let receiver = receiver.into();
@@ -73,6 +267,7 @@ impl BiddingContract {
             sender.clone(),
             self.0.clone(),
             &ExecuteMsg::Retract {
+                auction_id,
                 receiver: receiver.map(Addr::to_string),
             },
             &[],
@@ -81,28 +276,375 @@ impl BiddingContract {
         .map(|_| ())
     }
 
-    pub fn query_total_bids(&self, app: &App, address: &Addr) -> StdResult<Uint128> {
+    #[track_caller]
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_config(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+        denom: Option<Denom>,
+        commission: Option<Decimal>,
+        fee_mode: Option<FeeMode>,
+        fee_recipient: Option<&Addr>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::UpdateConfig {
+                auction_id,
+                denom,
+                commission,
+                fee_mode,
+                fee_recipient: fee_recipient.map(Addr::to_string),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn update_allowlist(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+        root: impl Into<Option<HexBinary>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::UpdateAllowlist {
+                auction_id,
+                root: root.into(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn deny(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+        addresses: &[&Addr],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Deny {
+                auction_id,
+                addresses: addresses.iter().map(|addr| addr.to_string()).collect(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn allow(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+        addresses: &[&Addr],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Allow {
+                auction_id,
+                addresses: addresses.iter().map(|addr| addr.to_string()).collect(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn propose_owner(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+        new_owner: &Addr,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::ProposeOwner {
+                auction_id,
+                new_owner: new_owner.to_string(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn accept_ownership(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::AcceptOwnership { auction_id },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn renounce_ownership(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::RenounceOwnership { auction_id },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn refund_losers<'a>(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        auction_id: u64,
+        start_after: impl Into<Option<&'a Addr>>,
+        limit: impl Into<Option<u32>>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::RefundLosers {
+                auction_id,
+                start_after: start_after.into().map(Addr::to_string),
+                limit: limit.into(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn pause(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        operation: Operation,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Pause { operation },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn unpause(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        operation: Operation,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecuteMsg::Unpause { operation },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn sudo(&self, app: &mut App, msg: &SudoMsg) -> Result<(), ContractError> {
+        app.wasm_sudo(self.0.clone(), msg)
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    pub fn query_total_bids(
+        &self,
+        app: &App,
+        auction_id: u64,
+        address: &Addr,
+    ) -> StdResult<Uint128> {
         app.wrap().query_wasm_smart(
             self.0.clone(),
             &QueryMsg::TotalBids {
+                auction_id,
+                address: address.to_string(),
+            },
+        )
+    }
+
+    pub fn query_highest_bid(&self, app: &App, auction_id: u64) -> StdResult<BidResponse> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::HighestBid { auction_id })
+    }
+
+    pub fn query_bidding_completed(&self, app: &App, auction_id: u64) -> StdResult<bool> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::BiddingCompleted { auction_id })
+    }
+
+    pub fn query_winning_bid(&self, app: &App, auction_id: u64) -> StdResult<BidResponse> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::WinningBid { auction_id })
+    }
+
+    pub fn query_end_time(&self, app: &App, auction_id: u64) -> StdResult<Expiration> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::EndTime { auction_id })
+    }
+
+    pub fn query_current_price(&self, app: &App, auction_id: u64) -> StdResult<Uint128> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::CurrentPrice { auction_id })
+    }
+
+    pub fn query_unrefunded_bids(&self, app: &App, auction_id: u64) -> StdResult<u64> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::UnrefundedBids { auction_id })
+    }
+
+    pub fn query_ownership(&self, app: &App, auction_id: u64) -> StdResult<OwnershipResponse> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Ownership { auction_id })
+    }
+
+    pub fn query_pause_state(&self, app: &App) -> StdResult<PauseStateResponse> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::PauseState {})
+    }
+
+    pub fn query_is_eligible(
+        &self,
+        app: &App,
+        auction_id: u64,
+        address: &Addr,
+        proof: Vec<HexBinary>,
+    ) -> StdResult<bool> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::IsEligible {
+                auction_id,
                 address: address.to_string(),
+                proof: Some(proof),
+            },
+        )
+    }
+
+    pub fn query_denied_addresses<'a>(
+        &self,
+        app: &App,
+        auction_id: u64,
+        start_after: impl Into<Option<&'a Addr>>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<DeniedAddressesResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::DeniedAddresses {
+                auction_id,
+                start_after: start_after.into().map(Addr::to_string),
+                limit: limit.into(),
             },
         )
     }
 
-    pub fn query_highest_bid(&self, app: &App) -> StdResult<BidResponse> {
+    pub fn query_fee_schedule(&self, app: &App, auction_id: u64) -> StdResult<FeeScheduleResponse> {
         app.wrap()
-            .query_wasm_smart(self.0.clone(), &QueryMsg::HighestBid {})
+            .query_wasm_smart(self.0.clone(), &QueryMsg::FeeSchedule { auction_id })
     }
 
-    pub fn query_bidding_completed(&self, app: &App) -> StdResult<bool> {
+    pub fn query_lot(&self, app: &App, auction_id: u64) -> StdResult<Lot> {
         app.wrap()
-            .query_wasm_smart(self.0.clone(), &QueryMsg::BiddingCompleted {})
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Lot { auction_id })
+    }
+
+    pub fn query_all_bids<'a>(
+        &self,
+        app: &App,
+        auction_id: u64,
+        start_after: impl Into<Option<&'a Addr>>,
+        limit: impl Into<Option<u32>>,
+        order: impl Into<Option<OrderBy>>,
+    ) -> StdResult<AllBidsResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::AllBids {
+                auction_id,
+                start_after: start_after.into().map(Addr::to_string),
+                limit: limit.into(),
+                order: order.into(),
+            },
+        )
     }
 
-    pub fn query_winning_bid(&self, app: &App) -> StdResult<BidResponse> {
+    pub fn query_top_bids(&self, app: &App, auction_id: u64, n: u32) -> StdResult<AllBidsResponse> {
         app.wrap()
-            .query_wasm_smart(self.0.clone(), &QueryMsg::WinningBid {})
+            .query_wasm_smart(self.0.clone(), &QueryMsg::TopBids { auction_id, n })
+    }
+
+    pub fn query_bid_history<'a>(
+        &self,
+        app: &App,
+        auction_id: u64,
+        start_after: impl Into<Option<u64>>,
+        limit: impl Into<Option<u32>>,
+        bidder: impl Into<Option<&'a Addr>>,
+    ) -> StdResult<BidHistoryResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::BidHistory {
+                auction_id,
+                start_after: start_after.into(),
+                limit: limit.into(),
+                bidder: bidder.into().map(Addr::to_string),
+            },
+        )
+    }
+
+    pub fn query_list_auctions<'a>(
+        &self,
+        app: &App,
+        status: impl Into<Option<Status>>,
+        owner: impl Into<Option<&'a Addr>>,
+        start_after: impl Into<Option<u64>>,
+        limit: impl Into<Option<u32>>,
+    ) -> StdResult<ListAuctionsResponse> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::ListAuctions {
+                status: status.into(),
+                owner: owner.into().map(Addr::to_string),
+                start_after: start_after.into(),
+                limit: limit.into(),
+            },
+        )
     }
 }
 
@@ -1,14 +1,124 @@
-use cosmwasm_std::{coins, Addr, Uint128};
-use cw_multi_test::App;
-
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    coin, coins, to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env, HexBinary,
+    MessageInfo, Response, StdResult, Uint128,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, Denom};
+use cw721::{Cw721QueryMsg, OwnerOfResponse};
+use cw_multi_test::{App, ContractWrapper, Executor};
+use cw_utils::{Duration, Expiration, Scheduled};
+use sha2::{Digest, Sha256};
+
+use crate::contract::commitment_hash;
 use crate::error::ContractError;
-use crate::msg::BidResponse;
-use crate::state::{Config, State, Status, CONFIG, STATE};
+use crate::migrations::{LegacyConfig, LegacyState, LEGACY_BIDS, LEGACY_CONFIG, LEGACY_STATE};
+use crate::msg::{
+    AuctionMsg, BidResponse, Cw20HookMsg, Cw721HookMsg, ExecuteMsg, FeeScheduleResponse,
+    InstantiateMsg, LotMsg, OrderBy, OwnershipResponse, PauseStateResponse, SudoMsg,
+};
+use crate::state::{
+    AuctionKind, BidIncrement, BidRecord, Cancellation, Config, DenomRate, FeeMode, Lot, Operation,
+    PauseState, Settlement, StandingBid, State, Status, CONFIGS, STATES,
+};
 
 use super::contract::BiddingContract;
 
 const ATOM: &str = "atom";
 
+/// Instantiates a cw721-base collection and mints `token_id` to `owner`
+fn mint_nft(app: &mut App, owner: &Addr, token_id: &str) -> Addr {
+    let contract = ContractWrapper::new(
+        cw721_base::entry::execute,
+        cw721_base::entry::instantiate,
+        cw721_base::entry::query,
+    );
+    let code_id = app.store_code(Box::new(contract));
+
+    let collection = app
+        .instantiate_contract(
+            code_id,
+            owner.clone(),
+            &cw721_base::InstantiateMsg {
+                name: "Lots".to_string(),
+                symbol: "LOT".to_string(),
+                minter: owner.to_string(),
+            },
+            &[],
+            "Collection",
+            None,
+        )
+        .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        collection.clone(),
+        &cw721_base::ExecuteMsg::<_, Empty>::Mint(cw721_base::MintMsg::<cw721_base::Extension> {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+        }),
+        &[],
+    )
+    .unwrap();
+
+    collection
+}
+
+/// Instantiates a cw20-base token with the given initial balances
+fn create_token(app: &mut App, balances: Vec<Cw20Coin>) -> Addr {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    let code_id = app.store_code(Box::new(contract));
+
+    app.instantiate_contract(
+        code_id,
+        Addr::unchecked("minter"),
+        &cw20_base::msg::InstantiateMsg {
+            name: "Token".to_string(),
+            symbol: "TKN".to_string(),
+            decimals: 6,
+            initial_balances: balances,
+            mint: None,
+            marketing: None,
+        },
+        &[],
+        "Token",
+        None,
+    )
+    .unwrap()
+}
+
+fn token_balance(app: &App, token: &Addr, address: &Addr) -> Uint128 {
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+    resp.balance
+}
+
+fn nft_owner(app: &App, collection: &Addr, token_id: &str) -> String {
+    let resp: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            collection,
+            &Cw721QueryMsg::OwnerOf {
+                token_id: token_id.to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    resp.owner
+}
+
 #[test]
 fn bidding_with_owner() {
     let owner = Addr::unchecked("owner");
@@ -17,35 +127,53 @@ fn bidding_with_owner() {
     let mut app = App::default();
 
     let code_id = BiddingContract::store_code(&mut app);
-    let contract = BiddingContract::instantiate(
-        &mut app,
-        code_id,
-        &sender,
-        "Bidding contract",
-        &owner,
-        ATOM,
-        1_000_000,
-    )
-    .unwrap();
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &sender, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &sender, &owner, ATOM, None)
+        .unwrap();
 
-    let state = STATE.query(&app.wrap(), contract.addr().clone()).unwrap();
+    let state = STATES
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
 
     assert_eq!(
         state,
         State {
             current_status: Status::Open,
             highest_bid: None,
+            runner_up: None,
+            end_time: Expiration::Never {},
         }
     );
 
-    let config = CONFIG.query(&app.wrap(), contract.addr().clone()).unwrap();
+    let config = CONFIGS
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
 
     assert_eq!(
         config,
         Config {
-            denom: ATOM.to_string(),
+            denom: Denom::Native(ATOM.to_string()),
             owner,
-            commission: 1_000_000,
+            kind: AuctionKind::English,
+            settlement: Settlement::FirstPrice,
+            commission: Decimal::zero(),
+            fee_mode: FeeMode::PerBid,
+            fee_recipient: None,
+            reserve_price: None,
+            opening_price: Uint128::zero(),
+            min_increment: None,
+            buy_now: None,
+            start_time: None,
+            end_time: Expiration::Never {},
+            extension_window: None,
+            keeper_reward: Decimal::zero(),
+            cancellation: Cancellation::Anytime,
+            allowlist: None,
+            accepted_denoms: vec![],
         }
     );
 }
@@ -57,35 +185,53 @@ fn bidding_with_sender() {
     let mut app = App::default();
     let code_id = BiddingContract::store_code(&mut app);
 
-    let contract = BiddingContract::instantiate(
-        &mut app,
-        code_id,
-        &owner,
-        "Bidding contract",
-        None,
-        ATOM,
-        1_000_000,
-    )
-    .unwrap();
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
 
-    let state = STATE.query(&app.wrap(), contract.addr().clone()).unwrap();
+    let state = STATES
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
 
     assert_eq!(
         state,
         State {
             current_status: Status::Open,
             highest_bid: None,
+            runner_up: None,
+            end_time: Expiration::Never {},
         }
     );
 
-    let config = CONFIG.query(&app.wrap(), contract.addr().clone()).unwrap();
+    let config = CONFIGS
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
 
     assert_eq!(
         config,
         Config {
-            denom: ATOM.to_string(),
+            denom: Denom::Native(ATOM.to_string()),
             owner,
-            commission: 1_000_000,
+            kind: AuctionKind::English,
+            settlement: Settlement::FirstPrice,
+            commission: Decimal::zero(),
+            fee_mode: FeeMode::PerBid,
+            fee_recipient: None,
+            reserve_price: None,
+            opening_price: Uint128::zero(),
+            min_increment: None,
+            buy_now: None,
+            start_time: None,
+            end_time: Expiration::Never {},
+            extension_window: None,
+            keeper_reward: Decimal::zero(),
+            cancellation: Cancellation::Anytime,
+            allowlist: None,
+            accepted_denoms: vec![],
         }
     );
 }
@@ -103,19 +249,14 @@ fn unauthorized_bid() {
     });
 
     let code_id = BiddingContract::store_code(&mut app);
-    let contract = BiddingContract::instantiate(
-        &mut app,
-        code_id,
-        &sender,
-        "Bidding contract",
-        &owner,
-        ATOM,
-        1_000_000,
-    )
-    .unwrap();
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &sender, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &sender, &owner, ATOM, None)
+        .unwrap();
 
     let err = contract
-        .bid(&mut app, &owner, &coins(1_000_000, ATOM))
+        .bid(&mut app, &owner, auction_id, &coins(1_000_000, ATOM))
         .unwrap_err();
 
     assert_eq!(
@@ -141,37 +282,30 @@ fn invalid_funds() {
     let mut app = App::new(|router, _api, storage| {
         router
             .bank
-            .init_balance(storage, &sender, coins(10_000_000, ATOM))
+            .init_balance(
+                storage,
+                &sender,
+                vec![coin(10_000_000, ATOM), coin(900_000, "btc")],
+            )
             .unwrap();
     });
 
     let code_id = BiddingContract::store_code(&mut app);
-    let contract = BiddingContract::instantiate(
-        &mut app,
-        code_id,
-        &owner,
-        "Bidding contract",
-        None,
-        ATOM,
-        1_000_000,
-    )
-    .unwrap();
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
 
     let err = contract
-        .bid(&mut app, &sender, &coins(900_000, ATOM))
+        .bid(&mut app, &sender, auction_id, &coins(900_000, "btc"))
         .unwrap_err();
 
-    assert_eq!(
-        err,
-        ContractError::InvalidCommission {
-            funds: Uint128::new(900_000),
-            commission: Uint128::new(1_000_000)
-        }
-    );
+    assert_eq!(err, ContractError::InvalidFunds);
 
     assert_eq!(
         app.wrap().query_all_balances(sender).unwrap(),
-        coins(10_000_000, ATOM)
+        vec![coin(10_000_000, ATOM), coin(900_000, "btc")]
     );
 
     assert_eq!(app.wrap().query_all_balances(owner).unwrap(), &[]);
@@ -192,19 +326,14 @@ fn bidding() {
     });
 
     let code_id = BiddingContract::store_code(&mut app);
-    let contract = BiddingContract::instantiate(
-        &mut app,
-        code_id,
-        &owner,
-        "Bidding contract",
-        None,
-        ATOM,
-        1_000_000,
-    )
-    .unwrap();
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
 
     contract
-        .bid(&mut app, &sender, &coins(14_000_000, ATOM))
+        .bid(&mut app, &sender, auction_id, &coins(14_000_000, ATOM))
         .unwrap();
 
     assert_eq!(
@@ -221,34 +350,39 @@ fn close_bidding() {
     let mut app = App::default();
 
     let code_id = BiddingContract::store_code(&mut app);
-    let contract = BiddingContract::instantiate(
-        &mut app,
-        code_id,
-        &sender,
-        "Bidding contract",
-        &owner,
-        ATOM,
-        1_000_000,
-    )
-    .unwrap();
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &sender, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &sender, &owner, ATOM, None)
+        .unwrap();
 
-    let state = STATE.query(&app.wrap(), contract.addr().clone()).unwrap();
+    let state = STATES
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
     assert_eq!(
         state,
         State {
             current_status: Status::Open,
-            highest_bid: None
+            highest_bid: None,
+            runner_up: None,
+            end_time: Expiration::Never {},
         }
     );
 
-    contract.close(&mut app, &owner).unwrap();
+    contract.close(&mut app, &owner, auction_id).unwrap();
 
-    let state = STATE.query(&app.wrap(), contract.addr().clone()).unwrap();
+    let state = STATES
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
     assert_eq!(
         state,
         State {
             current_status: Status::Closed,
-            highest_bid: None
+            highest_bid: None,
+            runner_up: None,
+            end_time: Expiration::Never {},
         }
     );
 }
@@ -261,27 +395,27 @@ fn unauthorized_closed() {
     let mut app = App::default();
 
     let code_id = BiddingContract::store_code(&mut app);
-    let contract = BiddingContract::instantiate(
-        &mut app,
-        code_id,
-        &owner,
-        "Bidding contract",
-        None,
-        ATOM,
-        1_000_000,
-    )
-    .unwrap();
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
 
-    let state = STATE.query(&app.wrap(), contract.addr().clone()).unwrap();
+    let state = STATES
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
     assert_eq!(
         state,
         State {
             current_status: Status::Open,
-            highest_bid: None
+            highest_bid: None,
+            runner_up: None,
+            end_time: Expiration::Never {},
         }
     );
 
-    let err = contract.close(&mut app, &sender).unwrap_err();
+    let err = contract.close(&mut app, &sender, auction_id).unwrap_err();
     assert_eq!(
         err,
         ContractError::Unauthorized {
@@ -289,12 +423,17 @@ fn unauthorized_closed() {
         }
     );
 
-    let state = STATE.query(&app.wrap(), contract.addr().clone()).unwrap();
+    let state = STATES
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
     assert_eq!(
         state,
         State {
             current_status: Status::Open,
-            highest_bid: None
+            highest_bid: None,
+            runner_up: None,
+            end_time: Expiration::Never {},
         }
     );
 }
@@ -312,26 +451,23 @@ fn query_total_bids() {
     });
 
     let code_id = BiddingContract::store_code(&mut app);
-    let contract = BiddingContract::instantiate(
-        &mut app,
-        code_id,
-        &owner,
-        "Bidding contract",
-        None,
-        ATOM,
-        1_000_000,
-    )
-    .unwrap();
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
 
     contract
-        .bid(&mut app, &sender, &coins(4_000_000, ATOM))
+        .bid(&mut app, &sender, auction_id, &coins(4_000_000, ATOM))
         .unwrap();
 
     contract
-        .bid(&mut app, &sender, &coins(4_000_000, ATOM))
+        .bid(&mut app, &sender, auction_id, &coins(4_000_000, ATOM))
         .unwrap();
 
-    let resp = contract.query_total_bids(&app, &sender).unwrap();
+    let resp = contract
+        .query_total_bids(&app, auction_id, &sender)
+        .unwrap();
     assert_eq!(resp, Uint128::new(8_000_000));
 }
 
@@ -353,26 +489,21 @@ fn query_highest_bid() {
     });
 
     let code_id = BiddingContract::store_code(&mut app);
-    let contract = BiddingContract::instantiate(
-        &mut app,
-        code_id,
-        &owner,
-        "Bidding contract",
-        None,
-        ATOM,
-        1_000_000,
-    )
-    .unwrap();
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
 
     contract
-        .bid(&mut app, &sender1, &coins(4_000_000, ATOM))
+        .bid(&mut app, &sender1, auction_id, &coins(4_000_000, ATOM))
         .unwrap();
 
     contract
-        .bid(&mut app, &sender2, &coins(5_000_000, ATOM))
+        .bid(&mut app, &sender2, auction_id, &coins(5_000_000, ATOM))
         .unwrap();
 
-    let resp = contract.query_highest_bid(&app).unwrap();
+    let resp = contract.query_highest_bid(&app, auction_id).unwrap();
     assert_eq!(
         resp,
         BidResponse {
@@ -395,27 +526,22 @@ fn query_winning_bid() {
     });
 
     let code_id = BiddingContract::store_code(&mut app);
-    let contract = BiddingContract::instantiate(
-        &mut app,
-        code_id,
-        &owner,
-        "Bidding contract",
-        None,
-        ATOM,
-        1_000_000,
-    )
-    .unwrap();
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
 
     contract
-        .bid(&mut app, &sender, &coins(9_000_000, ATOM))
+        .bid(&mut app, &sender, auction_id, &coins(9_000_000, ATOM))
         .unwrap();
 
-    contract.close(&mut app, &owner).unwrap();
+    contract.close(&mut app, &owner, auction_id).unwrap();
 
-    let resp = contract.query_bidding_completed(&app).unwrap();
-    assert_eq!(resp, true);
+    let resp = contract.query_bidding_completed(&app, auction_id).unwrap();
+    assert!(resp);
 
-    let resp = contract.query_winning_bid(&app).unwrap();
+    let resp = contract.query_winning_bid(&app, auction_id).unwrap();
     assert_eq!(
         resp,
         BidResponse {
@@ -424,3 +550,3316 @@ fn query_winning_bid() {
         }
     );
 }
+
+#[test]
+fn bidding_outside_schedule() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10_000_000, ATOM))
+            .unwrap();
+    });
+
+    let start = app.block_info().time.plus_seconds(100);
+    let end = start.plus_seconds(1_000);
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                start_time: Some(Scheduled::AtTime(start)),
+                end_time: Some(Expiration::AtTime(end)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let err = contract
+        .bid(&mut app, &sender, auction_id, &coins(1_000_000, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingNotStarted);
+
+    app.update_block(|block| block.time = start);
+    contract
+        .bid(&mut app, &sender, auction_id, &coins(1_000_000, ATOM))
+        .unwrap();
+
+    let resp = contract.query_bidding_completed(&app, auction_id).unwrap();
+    assert!(!resp);
+    contract.query_winning_bid(&app, auction_id).unwrap_err();
+
+    app.update_block(|block| block.time = end);
+    let err = contract
+        .bid(&mut app, &sender, auction_id, &coins(2_000_000, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed);
+
+    let resp = contract.query_bidding_completed(&app, auction_id).unwrap();
+    assert!(resp);
+
+    let resp = contract.query_winning_bid(&app, auction_id).unwrap();
+    assert_eq!(
+        resp,
+        BidResponse {
+            address: sender,
+            amount: Uint128::new(1_000_000)
+        }
+    );
+}
+
+#[test]
+fn invalid_schedule() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+    let now = app.block_info().time;
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+
+    let err = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                end_time: Some(Expiration::AtTime(now)),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidSchedule);
+
+    let err = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                start_time: Some(Scheduled::AtTime(now.plus_seconds(100))),
+                end_time: Some(Expiration::AtTime(now.plus_seconds(50))),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidSchedule);
+}
+
+#[test]
+fn settle_after_deadline() {
+    let owner = Addr::unchecked("owner");
+    let treasury = Addr::unchecked("treasury");
+    let sender = Addr::unchecked("sender");
+    let keeper = Addr::unchecked("keeper");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10_000_000, ATOM))
+            .unwrap();
+    });
+
+    let end = app.block_info().time.plus_seconds(1_000);
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+
+    // Keepers are paid out of the settlement fee, which per-bid fees leave nothing of
+    let err = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                end_time: Some(Expiration::AtTime(end)),
+                keeper_reward: Some(Decimal::percent(10)),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidKeeperReward);
+
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                commission: Some(Decimal::percent(10)),
+                fee_mode: Some(FeeMode::OnSettlement),
+                fee_recipient: Some(treasury.to_string()),
+                end_time: Some(Expiration::AtTime(end)),
+                keeper_reward: Some(Decimal::percent(10)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &sender, auction_id, &coins(10_000_000, ATOM))
+        .unwrap();
+
+    let err = contract.settle(&mut app, &keeper, auction_id).unwrap_err();
+    assert_eq!(err, ContractError::BiddingActive);
+
+    app.update_block(|block| block.time = end);
+    contract.settle(&mut app, &keeper, auction_id).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&keeper).unwrap(),
+        coins(100_000, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&treasury).unwrap(),
+        coins(900_000, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(9_000_000, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), &[]);
+
+    let err = contract.settle(&mut app, &keeper, auction_id).unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed);
+}
+
+#[test]
+fn keeper_reward_never_cuts_into_proceeds() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let keeper = Addr::unchecked("keeper");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(300, ATOM))
+            .unwrap();
+    });
+
+    let end = app.block_info().time.plus_seconds(1_000);
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                fee_mode: Some(FeeMode::OnSettlement),
+                end_time: Some(Expiration::AtTime(end)),
+                keeper_reward: Some(Decimal::one()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(300, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.time = end);
+    contract.settle(&mut app, &keeper, auction_id).unwrap();
+
+    // Without a fee there is nothing to reward, the owner is paid in full
+    assert_eq!(app.wrap().query_all_balances(&keeper).unwrap(), &[]);
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(300, ATOM)
+    );
+}
+
+#[test]
+fn settle_without_deadline() {
+    let owner = Addr::unchecked("owner");
+    let keeper = Addr::unchecked("keeper");
+
+    let mut app = App::default();
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+
+    let err = contract.settle(&mut app, &keeper, auction_id).unwrap_err();
+    assert_eq!(err, ContractError::BiddingActive);
+}
+
+#[test]
+fn bid_near_deadline_extends_it() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(10_000_000, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(10_000_000, ATOM))
+            .unwrap();
+    });
+
+    let end = app.block_info().time.plus_seconds(1_000);
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                end_time: Some(Expiration::AtTime(end)),
+                extension_window: Some(Duration::Time(300)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &sender1, auction_id, &coins(1_000_000, ATOM))
+        .unwrap();
+    let resp = contract.query_end_time(&app, auction_id).unwrap();
+    assert_eq!(resp, Expiration::AtTime(end));
+
+    let late = end.minus_seconds(10);
+    app.update_block(|block| block.time = late);
+    contract
+        .bid(&mut app, &sender2, auction_id, &coins(2_000_000, ATOM))
+        .unwrap();
+
+    let resp = contract.query_end_time(&app, auction_id).unwrap();
+    assert_eq!(resp, Expiration::AtTime(late.plus_seconds(300)));
+
+    app.update_block(|block| block.time = end);
+    let resp = contract.query_bidding_completed(&app, auction_id).unwrap();
+    assert!(!resp);
+
+    contract
+        .bid(&mut app, &sender1, auction_id, &coins(2_000_000, ATOM))
+        .unwrap();
+}
+
+#[test]
+fn multiple_auctions() {
+    let owner1 = Addr::unchecked("owner1");
+    let owner2 = Addr::unchecked("owner2");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10_000_000, ATOM))
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner1, "Bidding contract").unwrap();
+
+    let first = contract
+        .create_auction(&mut app, &owner1, None, ATOM, None)
+        .unwrap();
+    let second = contract
+        .create_auction(&mut app, &owner2, None, ATOM, None)
+        .unwrap();
+    let third = contract
+        .create_auction(&mut app, &owner1, None, ATOM, None)
+        .unwrap();
+    assert_eq!((first, second, third), (1, 2, 3));
+
+    contract
+        .bid(&mut app, &sender, first, &coins(3_000_000, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &sender, second, &coins(1_000_000, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        contract.query_total_bids(&app, first, &sender).unwrap(),
+        Uint128::new(3_000_000)
+    );
+    assert_eq!(
+        contract.query_total_bids(&app, second, &sender).unwrap(),
+        Uint128::new(1_000_000)
+    );
+    contract.query_highest_bid(&app, third).unwrap_err();
+
+    contract.close(&mut app, &owner1, first).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner1).unwrap(),
+        coins(3_000_000, ATOM)
+    );
+    assert!(!contract.query_bidding_completed(&app, second).unwrap());
+
+    let resp = contract
+        .query_list_auctions(&app, None, None, None, None)
+        .unwrap();
+    let ids: Vec<_> = resp.auctions.iter().map(|a| a.auction_id).collect();
+    assert_eq!(ids, vec![1, 2, 3]);
+
+    let resp = contract
+        .query_list_auctions(&app, Status::Open, &owner1, None, None)
+        .unwrap();
+    let ids: Vec<_> = resp.auctions.iter().map(|a| a.auction_id).collect();
+    assert_eq!(ids, vec![3]);
+
+    let resp = contract
+        .query_list_auctions(&app, None, None, first, 1)
+        .unwrap();
+    let ids: Vec<_> = resp.auctions.iter().map(|a| a.auction_id).collect();
+    assert_eq!(ids, vec![2]);
+}
+
+#[test]
+fn reserve_price_not_met() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(5_000_000, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(5_000_000, ATOM))
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                reserve_price: Some(Uint128::new(5_000_000)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &sender1, auction_id, &coins(2_000_000, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &sender2, auction_id, &coins(3_000_000, ATOM))
+        .unwrap();
+
+    contract.close(&mut app, &owner, auction_id).unwrap();
+
+    let state = STATES
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.current_status, Status::Failed);
+    assert!(contract.query_bidding_completed(&app, auction_id).unwrap());
+    contract.query_winning_bid(&app, auction_id).unwrap_err();
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), &[]);
+
+    let err = contract
+        .bid(&mut app, &sender1, auction_id, &coins(3_000_000, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed);
+
+    contract
+        .retract(&mut app, &sender1, auction_id, None)
+        .unwrap();
+    contract
+        .retract(&mut app, &sender2, auction_id, None)
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&sender1).unwrap(),
+        coins(5_000_000, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&sender2).unwrap(),
+        coins(5_000_000, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), &[]);
+
+    let err = contract
+        .retract(&mut app, &sender2, auction_id, None)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidRetract);
+}
+
+#[test]
+fn minimum_bid() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(10_000_000, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(10_000_000, ATOM))
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                opening_price: Some(Uint128::new(2_000_000)),
+                min_increment: Some(BidIncrement::Percent(Decimal::percent(10))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let err = contract
+        .bid(&mut app, &sender1, auction_id, &coins(1_000_000, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidBid {
+            existing: Uint128::zero(),
+            funds: Uint128::new(1_000_000),
+            new_bid: Uint128::new(1_000_000),
+            max_bid: Uint128::zero(),
+            min_bid: Uint128::new(2_000_000),
+        }
+    );
+
+    contract
+        .bid(&mut app, &sender1, auction_id, &coins(2_000_000, ATOM))
+        .unwrap();
+
+    let err = contract
+        .bid(&mut app, &sender2, auction_id, &coins(2_100_000, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidBid {
+            existing: Uint128::zero(),
+            funds: Uint128::new(2_100_000),
+            new_bid: Uint128::new(2_100_000),
+            max_bid: Uint128::new(2_000_000),
+            min_bid: Uint128::new(2_200_000),
+        }
+    );
+
+    contract
+        .bid(&mut app, &sender2, auction_id, &coins(2_200_000, ATOM))
+        .unwrap();
+}
+
+#[test]
+fn minimum_increment_bounds() {
+    let owner = Addr::unchecked("owner");
+    let whale = Addr::unchecked("whale");
+    let leader = Uint128::MAX / Uint128::new(2) + Uint128::one();
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &whale, coins(leader.u128(), ATOM))
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+
+    for min_increment in [
+        BidIncrement::Percent(Decimal::percent(300)),
+        BidIncrement::Percent(Decimal::zero()),
+        BidIncrement::Amount(Uint128::zero()),
+    ] {
+        let err = contract
+            .create_auction_with_msg(
+                &mut app,
+                &owner,
+                &AuctionMsg {
+                    denom: Denom::Native(ATOM.to_string()),
+                    min_increment: Some(min_increment),
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidIncrement);
+    }
+
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                min_increment: Some(BidIncrement::Percent(Decimal::one())),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &whale, auction_id, &coins(leader.u128(), ATOM))
+        .unwrap();
+
+    // Doubling the leader no longer fits, which is reported rather than panicking
+    contract.query_current_price(&app, auction_id).unwrap_err();
+}
+
+#[test]
+fn buy_now_closes_auction() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(10_000_000, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(10_000_000, ATOM))
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                buy_now: Some(Uint128::new(5_000_000)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &sender1, auction_id, &coins(1_000_000, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &sender2, auction_id, &coins(6_000_000, ATOM))
+        .unwrap();
+
+    assert!(contract.query_bidding_completed(&app, auction_id).unwrap());
+    assert_eq!(
+        contract.query_winning_bid(&app, auction_id).unwrap(),
+        BidResponse {
+            address: sender2.clone(),
+            amount: Uint128::new(5_000_000)
+        }
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(5_000_000, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&sender2).unwrap(),
+        coins(5_000_000, ATOM)
+    );
+
+    let err = contract
+        .bid(&mut app, &sender1, auction_id, &coins(6_000_000, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed);
+
+    contract
+        .retract(&mut app, &sender1, auction_id, None)
+        .unwrap();
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), &[]);
+
+    // The buy-now price still wins when it is less than a full increment above the leader
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                buy_now: Some(Uint128::new(100)),
+                min_increment: Some(BidIncrement::Amount(Uint128::new(10))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &sender1, auction_id, &coins(95, ATOM))
+        .unwrap();
+    assert_eq!(
+        contract.query_current_price(&app, auction_id).unwrap(),
+        Uint128::new(100)
+    );
+    contract
+        .bid(&mut app, &sender2, auction_id, &coins(100, ATOM))
+        .unwrap();
+
+    assert!(contract.query_bidding_completed(&app, auction_id).unwrap());
+    assert_eq!(
+        contract.query_winning_bid(&app, auction_id).unwrap(),
+        BidResponse {
+            address: sender2.clone(),
+            amount: Uint128::new(100)
+        }
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(5_000_100, ATOM)
+    );
+}
+
+#[test]
+fn reserve_miss_reported_before_settlement() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let end = app.block_info().time.plus_seconds(1_000);
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                reserve_price: Some(Uint128::new(500)),
+                end_time: Some(Expiration::AtTime(end)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(100, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.time = end);
+
+    // Nobody has settled yet, the auction is still marked open
+    let state = STATES
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.current_status, Status::Open);
+    assert!(contract.query_bidding_completed(&app, auction_id).unwrap());
+
+    let err = contract.query_winning_bid(&app, auction_id).unwrap_err();
+    assert!(err.to_string().contains("reserve price not met"));
+}
+
+#[test]
+fn buy_now_below_reserve() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let err = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                reserve_price: Some(Uint128::new(5_000_000)),
+                buy_now: Some(Uint128::new(4_000_000)),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBuyNow);
+}
+
+#[test]
+fn nft_lot_goes_to_winner() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10_000_000, ATOM))
+            .unwrap();
+    });
+
+    let collection = mint_nft(&mut app, &owner, "lot-1");
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                lot: Some(LotMsg {
+                    collection: collection.to_string(),
+                    token_id: "lot-1".to_string(),
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let err = contract
+        .bid(&mut app, &sender, auction_id, &coins(1_000_000, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::LotNotEscrowed);
+
+    app.execute_contract(
+        owner.clone(),
+        collection.clone(),
+        &cw721_base::ExecuteMsg::<cw721_base::Extension, Empty>::SendNft {
+            contract: contract.addr().to_string(),
+            token_id: "lot-1".to_string(),
+            msg: to_json_binary(&Cw721HookMsg::Escrow { auction_id }).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        contract.query_lot(&app, auction_id).unwrap(),
+        Lot {
+            collection: collection.clone(),
+            token_id: "lot-1".to_string(),
+            escrowed: true,
+        }
+    );
+    assert_eq!(
+        nft_owner(&app, &collection, "lot-1"),
+        contract.addr().to_string()
+    );
+
+    contract
+        .bid(&mut app, &sender, auction_id, &coins(1_000_000, ATOM))
+        .unwrap();
+    contract.close(&mut app, &owner, auction_id).unwrap();
+
+    assert_eq!(nft_owner(&app, &collection, "lot-1"), sender.to_string());
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(1_000_000, ATOM)
+    );
+}
+
+#[test]
+fn unsold_nft_lot_returns_to_owner() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+
+    let collection = mint_nft(&mut app, &owner, "lot-1");
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                lot: Some(LotMsg {
+                    collection: collection.to_string(),
+                    token_id: "lot-1".to_string(),
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        collection.clone(),
+        &cw721_base::ExecuteMsg::<cw721_base::Extension, Empty>::SendNft {
+            contract: contract.addr().to_string(),
+            token_id: "lot-1".to_string(),
+            msg: to_json_binary(&Cw721HookMsg::Escrow { auction_id }).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    contract.close(&mut app, &owner, auction_id).unwrap();
+
+    assert_eq!(nft_owner(&app, &collection, "lot-1"), owner.to_string());
+    assert!(!contract.query_lot(&app, auction_id).unwrap().escrowed);
+}
+
+#[test]
+fn bidding_in_cw20() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(10_000_000, ATOM))
+            .unwrap();
+    });
+
+    let token = create_token(
+        &mut app,
+        vec![
+            Cw20Coin {
+                address: sender1.to_string(),
+                amount: Uint128::new(5_000_000),
+            },
+            Cw20Coin {
+                address: sender2.to_string(),
+                amount: Uint128::new(5_000_000),
+            },
+        ],
+    );
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Cw20(token.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let err = contract
+        .bid(&mut app, &sender1, auction_id, &coins(1_000_000, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFunds);
+
+    for (sender, amount) in [(&sender1, 1_000_000), (&sender2, 2_000_000)] {
+        app.execute_contract(
+            sender.clone(),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contract.addr().to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&Cw20HookMsg::Bid {
+                    auction_id,
+                    proof: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    assert_eq!(
+        contract.query_highest_bid(&app, auction_id).unwrap(),
+        BidResponse {
+            address: sender2.clone(),
+            amount: Uint128::new(2_000_000)
+        }
+    );
+
+    contract.close(&mut app, &owner, auction_id).unwrap();
+    contract
+        .retract(&mut app, &sender1, auction_id, None)
+        .unwrap();
+
+    assert_eq!(token_balance(&app, &token, &owner), Uint128::new(2_000_000));
+    assert_eq!(
+        token_balance(&app, &token, &sender1),
+        Uint128::new(5_000_000)
+    );
+    assert_eq!(
+        token_balance(&app, &token, &sender2),
+        Uint128::new(3_000_000)
+    );
+    assert_eq!(
+        token_balance(&app, &token, contract.addr()),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn dutch_auction() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10_000_000, ATOM))
+            .unwrap();
+    });
+
+    let start = app.block_info().time;
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                kind: Some(AuctionKind::Dutch {
+                    start_price: Uint128::new(10_000_000),
+                    floor_price: Uint128::new(2_000_000),
+                    step: None,
+                }),
+                end_time: Some(Expiration::AtTime(start.plus_seconds(1_000))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        contract.query_current_price(&app, auction_id).unwrap(),
+        Uint128::new(10_000_000)
+    );
+
+    app.update_block(|block| block.time = start.plus_seconds(500));
+    assert_eq!(
+        contract.query_current_price(&app, auction_id).unwrap(),
+        Uint128::new(6_000_000)
+    );
+
+    let err = contract
+        .bid(&mut app, &sender, auction_id, &coins(5_000_000, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidBid {
+            existing: Uint128::zero(),
+            funds: Uint128::new(5_000_000),
+            new_bid: Uint128::new(5_000_000),
+            max_bid: Uint128::zero(),
+            min_bid: Uint128::new(6_000_000),
+        }
+    );
+
+    contract
+        .bid(&mut app, &sender, auction_id, &coins(7_000_000, ATOM))
+        .unwrap();
+
+    assert!(contract.query_bidding_completed(&app, auction_id).unwrap());
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(6_000_000, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&sender).unwrap(),
+        coins(4_000_000, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), &[]);
+}
+
+#[test]
+fn stepwise_dutch_price() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+    let start = app.block_info().time.plus_seconds(100);
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                kind: Some(AuctionKind::Dutch {
+                    start_price: Uint128::new(10_000_000),
+                    floor_price: Uint128::new(2_000_000),
+                    step: Some(300),
+                }),
+                start_time: Some(Scheduled::AtTime(start)),
+                end_time: Some(Expiration::AtTime(start.plus_seconds(1_000))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    app.update_block(|block| block.time = start.plus_seconds(299));
+    assert_eq!(
+        contract.query_current_price(&app, auction_id).unwrap(),
+        Uint128::new(10_000_000)
+    );
+
+    app.update_block(|block| block.time = start.plus_seconds(650));
+    assert_eq!(
+        contract.query_current_price(&app, auction_id).unwrap(),
+        Uint128::new(5_200_000)
+    );
+
+    app.update_block(|block| block.time = start.plus_seconds(1_000));
+    assert_eq!(
+        contract.query_current_price(&app, auction_id).unwrap(),
+        Uint128::new(2_000_000)
+    );
+}
+
+#[test]
+fn sealed_auction() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in [&alice, &bob, &carol] {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let start = app.block_info().time;
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                kind: Some(AuctionKind::Sealed {
+                    reveal_period: Duration::Time(100),
+                    forfeit: Decimal::percent(10),
+                }),
+                end_time: Some(Expiration::AtTime(start.plus_seconds(100))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let err = contract
+        .bid(&mut app, &alice, auction_id, &coins(100, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::WrongAuctionKind);
+
+    contract
+        .commit(
+            &mut app,
+            &alice,
+            auction_id,
+            commitment_hash(&alice, Uint128::new(300), "alice"),
+            &coins(500, ATOM),
+        )
+        .unwrap();
+    contract
+        .commit(
+            &mut app,
+            &bob,
+            auction_id,
+            commitment_hash(&bob, Uint128::new(400), "bob"),
+            &coins(400, ATOM),
+        )
+        .unwrap();
+    contract
+        .commit(
+            &mut app,
+            &carol,
+            auction_id,
+            commitment_hash(&carol, Uint128::new(900), "carol"),
+            &coins(1_000, ATOM),
+        )
+        .unwrap();
+
+    // Nothing can be revealed while commitments are still coming in
+    let err = contract
+        .reveal(&mut app, &alice, auction_id, 300, "alice")
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingActive);
+
+    app.update_block(|block| block.time = start.plus_seconds(150));
+
+    let err = contract
+        .commit(
+            &mut app,
+            &alice,
+            auction_id,
+            commitment_hash(&alice, Uint128::new(300), "alice"),
+            &coins(100, ATOM),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed);
+
+    let err = contract
+        .reveal(&mut app, &alice, auction_id, 350, "alice")
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidReveal);
+
+    contract
+        .reveal(&mut app, &alice, auction_id, 300, "alice")
+        .unwrap();
+    contract
+        .reveal(&mut app, &bob, auction_id, 400, "bob")
+        .unwrap();
+
+    assert!(!contract.query_bidding_completed(&app, auction_id).unwrap());
+    let err = contract.settle(&mut app, &owner, auction_id).unwrap_err();
+    assert_eq!(err, ContractError::BiddingActive);
+
+    // Carol never reveals
+    app.update_block(|block| block.time = start.plus_seconds(200));
+    assert!(contract.query_bidding_completed(&app, auction_id).unwrap());
+
+    contract.settle(&mut app, &owner, auction_id).unwrap();
+    assert_eq!(
+        contract.query_winning_bid(&app, auction_id).unwrap(),
+        BidResponse {
+            address: bob.clone(),
+            amount: Uint128::new(400),
+        }
+    );
+
+    contract
+        .retract(&mut app, &alice, auction_id, None)
+        .unwrap();
+    contract
+        .retract(&mut app, &carol, auction_id, None)
+        .unwrap();
+    contract
+        .retract(&mut app, &bob, auction_id, None)
+        .unwrap_err();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(500, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&alice).unwrap(),
+        coins(1_000, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&bob).unwrap(),
+        coins(600, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&carol).unwrap(),
+        coins(900, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), &[]);
+}
+
+#[test]
+fn sealed_reveal_above_deposit() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(1_000, ATOM))
+            .unwrap();
+    });
+
+    let start = app.block_info().time;
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                kind: Some(AuctionKind::Sealed {
+                    reveal_period: Duration::Time(100),
+                    forfeit: Decimal::zero(),
+                }),
+                end_time: Some(Expiration::AtTime(start.plus_seconds(100))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .commit(
+            &mut app,
+            &sender,
+            auction_id,
+            commitment_hash(&sender, Uint128::new(800), "salt"),
+            &coins(500, ATOM),
+        )
+        .unwrap();
+
+    app.update_block(|block| block.time = start.plus_seconds(150));
+
+    let err = contract
+        .reveal(&mut app, &sender, auction_id, 800, "salt")
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientDeposit {
+            deposit: Uint128::new(500),
+            amount: Uint128::new(800),
+        }
+    );
+}
+
+#[test]
+fn second_price_settlement() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                settlement: Some(Settlement::SecondPrice),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(100, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &bob, auction_id, &coins(150, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(100, ATOM))
+        .unwrap();
+
+    contract.close(&mut app, &owner, auction_id).unwrap();
+
+    // Alice wins with 200 but only pays Bob's 150
+    assert_eq!(
+        contract.query_winning_bid(&app, auction_id).unwrap(),
+        BidResponse {
+            address: alice.clone(),
+            amount: Uint128::new(200),
+        }
+    );
+    assert_eq!(
+        contract.query_total_bids(&app, auction_id, &alice).unwrap(),
+        Uint128::new(50)
+    );
+
+    contract
+        .retract(&mut app, &alice, auction_id, None)
+        .unwrap();
+    contract.retract(&mut app, &bob, auction_id, None).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(150, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&alice).unwrap(),
+        coins(850, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&bob).unwrap(),
+        coins(1_000, ATOM)
+    );
+}
+
+#[test]
+fn sealed_second_price_pays_reserve() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let start = app.block_info().time;
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                kind: Some(AuctionKind::Sealed {
+                    reveal_period: Duration::Time(100),
+                    forfeit: Decimal::zero(),
+                }),
+                settlement: Some(Settlement::SecondPrice),
+                reserve_price: Some(Uint128::new(300)),
+                end_time: Some(Expiration::AtTime(start.plus_seconds(100))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    for (bidder, amount) in [(&alice, 500), (&bob, 200)] {
+        contract
+            .commit(
+                &mut app,
+                bidder,
+                auction_id,
+                commitment_hash(bidder, Uint128::new(amount), "salt"),
+                &coins(amount, ATOM),
+            )
+            .unwrap();
+    }
+
+    app.update_block(|block| block.time = start.plus_seconds(150));
+    contract
+        .reveal(&mut app, &bob, auction_id, 200, "salt")
+        .unwrap();
+    contract
+        .reveal(&mut app, &alice, auction_id, 500, "salt")
+        .unwrap();
+
+    app.update_block(|block| block.time = start.plus_seconds(200));
+    contract.settle(&mut app, &bob, auction_id).unwrap();
+
+    // The runner-up bid is below the reserve, so the reserve is the price
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(300, ATOM)
+    );
+    assert_eq!(
+        contract.query_total_bids(&app, auction_id, &alice).unwrap(),
+        Uint128::new(200)
+    );
+}
+
+#[test]
+fn sealed_auction_charges_commission_on_settlement() {
+    let owner = Addr::unchecked("owner");
+    let treasury = Addr::unchecked("treasury");
+    let alice = Addr::unchecked("alice");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(1_000, ATOM))
+            .unwrap();
+    });
+
+    let start = app.block_info().time;
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+
+    let sealed = AuctionMsg {
+        denom: Denom::Native(ATOM.to_string()),
+        kind: Some(AuctionKind::Sealed {
+            reveal_period: Duration::Time(100),
+            forfeit: Decimal::zero(),
+        }),
+        commission: Some(Decimal::percent(10)),
+        fee_recipient: Some(treasury.to_string()),
+        end_time: Some(Expiration::AtTime(start.plus_seconds(100))),
+        ..Default::default()
+    };
+
+    let err = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                fee_mode: Some(FeeMode::PerBid),
+                ..sealed.clone()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFeeMode);
+
+    let auction_id = contract
+        .create_auction_with_msg(&mut app, &owner, &sealed)
+        .unwrap();
+    assert_eq!(
+        contract.query_fee_schedule(&app, auction_id).unwrap(),
+        FeeScheduleResponse {
+            commission: Decimal::percent(10),
+            fee_mode: FeeMode::OnSettlement,
+            fee_recipient: treasury.clone(),
+        }
+    );
+
+    let err = contract
+        .update_config(
+            &mut app,
+            &owner,
+            auction_id,
+            None,
+            None,
+            Some(FeeMode::PerBid),
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFeeMode);
+
+    contract
+        .commit(
+            &mut app,
+            &alice,
+            auction_id,
+            commitment_hash(&alice, Uint128::new(300), "salt"),
+            &coins(300, ATOM),
+        )
+        .unwrap();
+
+    app.update_block(|block| block.time = start.plus_seconds(150));
+    contract
+        .reveal(&mut app, &alice, auction_id, 300, "salt")
+        .unwrap();
+
+    app.update_block(|block| block.time = start.plus_seconds(200));
+    contract.settle(&mut app, &alice, auction_id).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&treasury).unwrap(),
+        coins(30, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(270, ATOM)
+    );
+}
+
+#[test]
+fn commission_on_bids() {
+    let owner = Addr::unchecked("owner");
+    let treasury = Addr::unchecked("treasury");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(1_000, ATOM))
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+
+    let err = contract
+        .create_auction(&mut app, &owner, None, ATOM, Decimal::percent(101))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidCommission);
+
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                commission: Some(Decimal::percent(5)),
+                fee_recipient: Some(treasury.to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        contract.query_fee_schedule(&app, auction_id).unwrap(),
+        FeeScheduleResponse {
+            commission: Decimal::percent(5),
+            fee_mode: FeeMode::PerBid,
+            fee_recipient: treasury.clone(),
+        }
+    );
+
+    contract
+        .bid(&mut app, &sender, auction_id, &coins(200, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &sender, auction_id, &coins(100, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        contract
+            .query_total_bids(&app, auction_id, &sender)
+            .unwrap(),
+        Uint128::new(285)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&treasury).unwrap(),
+        coins(15, ATOM)
+    );
+
+    contract.close(&mut app, &owner, auction_id).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(285, ATOM)
+    );
+}
+
+#[test]
+fn buy_now_change_pays_no_commission() {
+    let owner = Addr::unchecked("owner");
+    let treasury = Addr::unchecked("treasury");
+    let alice = Addr::unchecked("alice");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(1_000, ATOM))
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                commission: Some(Decimal::percent(10)),
+                fee_recipient: Some(treasury.to_string()),
+                buy_now: Some(Uint128::new(100)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    // 200 sent, 180 after commission; only the 100 going into the price pays its share
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(200, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&treasury).unwrap(),
+        coins(11, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(100, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&alice).unwrap(),
+        coins(889, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), &[]);
+
+    // History keeps what was bid, the price paid is reported by the settlement
+    let history = contract
+        .query_bid_history(&app, auction_id, None, None, None)
+        .unwrap();
+    assert_eq!(
+        history
+            .bids
+            .iter()
+            .map(|record| (record.increment, record.total))
+            .collect::<Vec<_>>(),
+        vec![(Uint128::new(180), Uint128::new(180))]
+    );
+}
+
+#[test]
+fn commission_on_settlement() {
+    let owner = Addr::unchecked("owner");
+    let treasury = Addr::unchecked("treasury");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                commission: Some(Decimal::percent(10)),
+                fee_mode: Some(FeeMode::OnSettlement),
+                fee_recipient: Some(treasury.to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(300, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &bob, auction_id, &coins(500, ATOM))
+        .unwrap();
+    assert_eq!(app.wrap().query_all_balances(&treasury).unwrap(), &[]);
+
+    let resp = app
+        .execute_contract(
+            owner.clone(),
+            contract.addr().clone(),
+            &ExecuteMsg::Close { auction_id },
+            &[],
+        )
+        .unwrap();
+    let wasm = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    for (key, value) in [("gross", "500"), ("fee", "50"), ("net", "450")] {
+        assert!(wasm
+            .attributes
+            .iter()
+            .any(|attr| attr.key == key && attr.value == value));
+    }
+
+    contract
+        .retract(&mut app, &alice, auction_id, None)
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&alice).unwrap(),
+        coins(1_000, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&treasury).unwrap(),
+        coins(50, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(450, ATOM)
+    );
+}
+
+#[test]
+fn refund_losers_in_pages() {
+    let owner = Addr::unchecked("owner");
+    let keeper = Addr::unchecked("keeper");
+    let bidders: Vec<_> = (1..=5)
+        .map(|idx| Addr::unchecked(format!("bidder{idx}")))
+        .collect();
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in &bidders {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+
+    for (idx, bidder) in bidders.iter().enumerate() {
+        contract
+            .bid(
+                &mut app,
+                bidder,
+                auction_id,
+                &coins(100 * (idx as u128 + 1), ATOM),
+            )
+            .unwrap();
+    }
+
+    let err = contract
+        .refund_losers(&mut app, &keeper, auction_id, None, None)
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingActive);
+
+    contract.close(&mut app, &owner, auction_id).unwrap();
+    assert_eq!(contract.query_unrefunded_bids(&app, auction_id).unwrap(), 4);
+
+    contract
+        .refund_losers(&mut app, &keeper, auction_id, None, 2)
+        .unwrap();
+    assert_eq!(contract.query_unrefunded_bids(&app, auction_id).unwrap(), 2);
+
+    contract
+        .refund_losers(&mut app, &keeper, auction_id, &bidders[1], None)
+        .unwrap();
+    assert_eq!(contract.query_unrefunded_bids(&app, auction_id).unwrap(), 0);
+
+    for bidder in &bidders[..4] {
+        assert_eq!(
+            app.wrap().query_all_balances(bidder).unwrap(),
+            coins(1_000, ATOM)
+        );
+    }
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(500, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), &[]);
+}
+
+#[test]
+fn bid_history() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+
+    let start = app.block_info();
+
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(100, ATOM))
+        .unwrap();
+    app.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(5);
+    });
+    contract
+        .bid(&mut app, &bob, auction_id, &coins(200, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(150, ATOM))
+        .unwrap();
+
+    let history = contract
+        .query_bid_history(&app, auction_id, None, None, None)
+        .unwrap();
+    assert_eq!(history.bids.len(), 3);
+    assert_eq!(
+        history.bids[0],
+        BidRecord {
+            auction_id,
+            seq: 1,
+            bidder: alice.clone(),
+            increment: Uint128::new(100),
+            total: Uint128::new(100),
+            height: start.height,
+            time: start.time,
+        }
+    );
+
+    let history = contract
+        .query_bid_history(&app, auction_id, None, None, &alice)
+        .unwrap();
+    assert_eq!(
+        history
+            .bids
+            .iter()
+            .map(|record| (record.seq, record.increment, record.total))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, Uint128::new(100), Uint128::new(100)),
+            (3, Uint128::new(150), Uint128::new(250)),
+        ]
+    );
+
+    let history = contract
+        .query_bid_history(&app, auction_id, 1, 1, None)
+        .unwrap();
+    assert_eq!(history.bids.len(), 1);
+    assert_eq!(history.bids[0].bidder, bob);
+    assert_eq!(history.bids[0].time, start.time.plus_seconds(5));
+
+    let history = contract
+        .query_bid_history(&app, auction_id, 1, None, &alice)
+        .unwrap();
+    assert_eq!(history.bids.len(), 1);
+    assert_eq!(history.bids[0].seq, 3);
+}
+
+#[test]
+fn all_bids_ranked_by_amount() {
+    let owner = Addr::unchecked("owner");
+    let bidders: Vec<_> = ["alice", "bob", "carol", "dave"]
+        .into_iter()
+        .map(Addr::unchecked)
+        .collect();
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in &bidders {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+    // Bids on another auction must not show up
+    let other_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+    contract
+        .bid(&mut app, &bidders[3], other_id, &coins(900, ATOM))
+        .unwrap();
+
+    // alice 100, bob 200, carol 300, then alice tops up to 400
+    for (bidder, amount) in [(0, 100), (1, 200), (2, 300), (0, 300)] {
+        contract
+            .bid(&mut app, &bidders[bidder], auction_id, &coins(amount, ATOM))
+            .unwrap();
+    }
+
+    let ranked = |bids: Vec<BidResponse>| {
+        bids.into_iter()
+            .map(|bid| (bid.address.to_string(), bid.amount.u128()))
+            .collect::<Vec<_>>()
+    };
+
+    let page = contract
+        .query_all_bids(&app, auction_id, None, 2, None)
+        .unwrap();
+    assert_eq!(
+        ranked(page.bids),
+        vec![("alice".to_string(), 400), ("carol".to_string(), 300)]
+    );
+
+    let page = contract
+        .query_all_bids(&app, auction_id, &bidders[2], None, None)
+        .unwrap();
+    assert_eq!(ranked(page.bids), vec![("bob".to_string(), 200)]);
+
+    let page = contract
+        .query_all_bids(&app, auction_id, &bidders[1], None, OrderBy::Ascending)
+        .unwrap();
+    assert_eq!(
+        ranked(page.bids),
+        vec![("carol".to_string(), 300), ("alice".to_string(), 400)]
+    );
+
+    let top = contract.query_top_bids(&app, auction_id, 1).unwrap();
+    assert_eq!(ranked(top.bids), vec![("alice".to_string(), 400)]);
+
+    assert_eq!(
+        contract
+            .query_total_bids(&app, auction_id, &bidders[3])
+            .unwrap(),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn update_config_before_first_bid() {
+    let owner = Addr::unchecked("owner");
+    let treasury = Addr::unchecked("treasury");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(1_000, "uusd"))
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+
+    let err = contract
+        .update_config(
+            &mut app,
+            &sender,
+            auction_id,
+            Some(Denom::Native("uusd".to_string())),
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+
+    contract
+        .update_config(
+            &mut app,
+            &owner,
+            auction_id,
+            Some(Denom::Native("uusd".to_string())),
+            Some(Decimal::percent(10)),
+            None,
+            None,
+        )
+        .unwrap();
+
+    contract
+        .bid(&mut app, &sender, auction_id, &coins(500, "uusd"))
+        .unwrap();
+
+    let err = contract
+        .update_config(
+            &mut app,
+            &owner,
+            auction_id,
+            Some(Denom::Native(ATOM.to_string())),
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::ConfigLocked);
+
+    // Where the fees go can still be corrected
+    contract
+        .update_config(
+            &mut app,
+            &owner,
+            auction_id,
+            None,
+            None,
+            None,
+            Some(&treasury),
+        )
+        .unwrap();
+
+    assert_eq!(
+        contract.query_fee_schedule(&app, auction_id).unwrap(),
+        FeeScheduleResponse {
+            commission: Decimal::percent(10),
+            fee_mode: FeeMode::PerBid,
+            fee_recipient: treasury,
+        }
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(50, "uusd")
+    );
+}
+
+#[test]
+fn two_step_ownership_transfer() {
+    let owner = Addr::unchecked("owner");
+    let new_owner = Addr::unchecked("new_owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(1_000, ATOM))
+            .unwrap();
+    });
+
+    let start = app.block_info().time;
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                end_time: Some(Expiration::AtTime(start.plus_seconds(100))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let err = contract
+        .accept_ownership(&mut app, &new_owner, auction_id)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoPendingOwner);
+
+    contract
+        .propose_owner(&mut app, &owner, auction_id, &new_owner)
+        .unwrap();
+
+    let err = contract
+        .accept_ownership(&mut app, &sender, auction_id)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: new_owner.to_string()
+        }
+    );
+
+    contract
+        .accept_ownership(&mut app, &new_owner, auction_id)
+        .unwrap();
+    assert_eq!(
+        contract.query_ownership(&app, auction_id).unwrap(),
+        OwnershipResponse {
+            owner: new_owner.clone(),
+            pending_owner: None,
+            renounced: false,
+        }
+    );
+
+    contract
+        .bid(&mut app, &sender, auction_id, &coins(300, ATOM))
+        .unwrap();
+
+    contract
+        .renounce_ownership(&mut app, &new_owner, auction_id)
+        .unwrap();
+    let err = contract
+        .close(&mut app, &new_owner, auction_id)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: new_owner.to_string()
+        }
+    );
+
+    // Proceeds still go to the renounced owner once the auction runs out
+    app.update_block(|block| block.time = start.plus_seconds(100));
+    contract.settle(&mut app, &sender, auction_id).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&new_owner).unwrap(),
+        coins(300, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), &[]);
+}
+
+#[cw_serde]
+struct LegacyInstantiateMsg {
+    contract: String,
+    version: String,
+}
+
+/// Deploys an auction in the original singleton layout, reporting the given cw2 version.
+/// Alice leads with 300 over Bob's 200, both held by the contract.
+fn deploy_legacy(app: &mut App, owner: &Addr, contract: &str, version: &str) -> Addr {
+    fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: LegacyInstantiateMsg,
+    ) -> StdResult<Response> {
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        cw2::set_contract_version(deps.storage, msg.contract, msg.version)?;
+
+        LEGACY_CONFIG.save(
+            deps.storage,
+            &LegacyConfig {
+                owner: info.sender,
+                denom: ATOM.to_string(),
+                commission: 0,
+            },
+        )?;
+        LEGACY_STATE.save(
+            deps.storage,
+            &LegacyState {
+                current_status: Status::Open,
+                highest_bid: Some((alice.clone(), Uint128::new(300))),
+            },
+        )?;
+        LEGACY_BIDS.save(deps.storage, &alice, &Uint128::new(300))?;
+        LEGACY_BIDS.save(deps.storage, &bob, &Uint128::new(200))?;
+
+        Ok(Response::new())
+    }
+
+    fn execute(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn query(_: Deps, _: Env, _: Empty) -> StdResult<Binary> {
+        Ok(Binary::default())
+    }
+
+    let code_id = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+
+    app.init_modules(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, owner, coins(500, ATOM))
+            .unwrap();
+    });
+
+    app.instantiate_contract(
+        code_id,
+        owner.clone(),
+        &LegacyInstantiateMsg {
+            contract: contract.to_string(),
+            version: version.to_string(),
+        },
+        &coins(500, ATOM),
+        "Legacy bidding contract",
+        Some(owner.to_string()),
+    )
+    .unwrap()
+}
+
+#[test]
+fn migrate_from_0_1_0() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::default();
+
+    let legacy = deploy_legacy(&mut app, &owner, "bidding-contract", "0.1.0");
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::migrate(&mut app, code_id, &owner, &legacy).unwrap();
+
+    let version = cw2::query_contract_info(&app.wrap(), contract.addr()).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    let state = STATES
+        .query(&app.wrap(), contract.addr().clone(), 1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        state.highest_bid,
+        Some(StandingBid {
+            bidder: alice.clone(),
+            amount: Uint128::new(300),
+        })
+    );
+    assert_eq!(
+        contract.query_total_bids(&app, 1, &bob).unwrap(),
+        Uint128::new(200)
+    );
+
+    // New auctions are numbered after the migrated one
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+    assert_eq!(auction_id, 2);
+
+    contract.close(&mut app, &owner, 1).unwrap();
+    contract.retract(&mut app, &bob, 1, None).unwrap();
+    contract.retract(&mut app, &alice, 1, None).unwrap_err();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(300, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&bob).unwrap(),
+        coins(200, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(contract.addr()).unwrap(), &[]);
+}
+
+#[test]
+fn migrate_refuses_downgrade_and_other_contracts() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let newer = deploy_legacy(&mut app, &owner, "bidding-contract", "9.0.0");
+    let err = BiddingContract::migrate(&mut app, code_id, &owner, &newer).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CannotDowngrade {
+            stored: "9.0.0".to_string(),
+            current: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
+
+    let other = deploy_legacy(&mut app, &owner, "crates.io:cw20-base", "0.1.0");
+    let err = BiddingContract::migrate(&mut app, code_id, &owner, &other).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WrongContract {
+            expected: "bidding-contract".to_string(),
+            found: "crates.io:cw20-base".to_string(),
+        }
+    );
+}
+
+#[test]
+fn sudo_cancel_frees_every_bid() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(300, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &bob, auction_id, &coins(400, ATOM))
+        .unwrap();
+
+    let resp = app
+        .wasm_sudo(contract.addr().clone(), &SudoMsg::Cancel { auction_id })
+        .unwrap();
+    let wasm = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "action" && attr.value == "sudo_cancel"));
+
+    let state = STATES
+        .query(&app.wrap(), contract.addr().clone(), auction_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.current_status, Status::Cancelled);
+    contract.query_winning_bid(&app, auction_id).unwrap_err();
+
+    // Nothing is paid out by the cancel itself, bidders are refunded in full afterwards
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(700, ATOM)
+    );
+    contract
+        .refund_losers(&mut app, &owner, auction_id, None, 1)
+        .unwrap();
+    contract.retract(&mut app, &bob, auction_id, None).unwrap();
+
+    for bidder in [&alice, &bob] {
+        assert_eq!(
+            app.wrap().query_all_balances(bidder).unwrap(),
+            coins(1_000, ATOM)
+        );
+    }
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), &[]);
+
+    let err = contract
+        .sudo(&mut app, &SudoMsg::ForceClose { auction_id })
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed);
+}
+
+#[test]
+fn sudo_force_close_waits_for_reveals() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(1_000, ATOM))
+            .unwrap();
+    });
+
+    let start = app.block_info().time;
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                kind: Some(AuctionKind::Sealed {
+                    reveal_period: Duration::Time(100),
+                    forfeit: Decimal::percent(50),
+                }),
+                end_time: Some(Expiration::AtTime(start.plus_seconds(100))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .commit(
+            &mut app,
+            &alice,
+            auction_id,
+            commitment_hash(&alice, Uint128::new(1_000), "alice"),
+            &coins(1_000, ATOM),
+        )
+        .unwrap();
+
+    // Neither while committing nor while revealing
+    let err = contract
+        .sudo(&mut app, &SudoMsg::ForceClose { auction_id })
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingActive);
+
+    app.update_block(|block| block.time = start.plus_seconds(150));
+    let err = contract
+        .sudo(&mut app, &SudoMsg::ForceClose { auction_id })
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingActive);
+
+    contract
+        .reveal(&mut app, &alice, auction_id, 1_000, "alice")
+        .unwrap();
+
+    app.update_block(|block| block.time = start.plus_seconds(200));
+    contract
+        .sudo(&mut app, &SudoMsg::ForceClose { auction_id })
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(1_000, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(&alice).unwrap(), &[]);
+}
+
+#[test]
+fn sudo_overrides() {
+    let owner = Addr::unchecked("owner");
+    let new_owner = Addr::unchecked("new_owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(1_000, ATOM))
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, Decimal::percent(20))
+        .unwrap();
+
+    contract
+        .sudo(
+            &mut app,
+            &SudoMsg::SetFeeCap {
+                fee_cap: Decimal::percent(5),
+            },
+        )
+        .unwrap();
+
+    // Above the cap, new auctions are refused and existing ones charge the cap
+    let err = contract
+        .create_auction(&mut app, &owner, None, ATOM, Decimal::percent(10))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidCommission);
+    assert_eq!(
+        contract
+            .query_fee_schedule(&app, auction_id)
+            .unwrap()
+            .commission,
+        Decimal::percent(5)
+    );
+
+    contract
+        .bid(&mut app, &sender, auction_id, &coins(200, ATOM))
+        .unwrap();
+
+    contract
+        .sudo(
+            &mut app,
+            &SudoMsg::ReassignOwner {
+                auction_id,
+                new_owner: new_owner.to_string(),
+            },
+        )
+        .unwrap();
+    contract
+        .sudo(&mut app, &SudoMsg::ForceClose { auction_id })
+        .unwrap();
+
+    // The 10 fee went to the owner at the time of the bid, the sale to the new one
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&new_owner).unwrap(),
+        coins(190, ATOM)
+    );
+}
+
+#[test]
+fn owner_cancels_auction() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(300, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &bob, auction_id, &coins(400, ATOM))
+        .unwrap();
+
+    let err = contract.cancel(&mut app, &alice, auction_id).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+
+    contract.cancel(&mut app, &owner, auction_id).unwrap();
+
+    let err = contract
+        .bid(&mut app, &alice, auction_id, &coins(500, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed);
+    let err = contract.close(&mut app, &owner, auction_id).unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed);
+
+    // The leader gets everything back as well
+    contract.retract(&mut app, &bob, auction_id, None).unwrap();
+    contract
+        .retract(&mut app, &alice, auction_id, None)
+        .unwrap();
+
+    for bidder in [&alice, &bob] {
+        assert_eq!(
+            app.wrap().query_all_balances(bidder).unwrap(),
+            coins(1_000, ATOM)
+        );
+    }
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), &[]);
+}
+
+#[test]
+fn restricted_cancellation() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(1_000, ATOM))
+            .unwrap();
+    });
+
+    let start = app.block_info().time;
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+
+    let before_first_bid = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                cancellation: Some(Cancellation::BeforeFirstBid),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    contract
+        .bid(&mut app, &sender, before_first_bid, &coins(100, ATOM))
+        .unwrap();
+    let err = contract
+        .cancel(&mut app, &owner, before_first_bid)
+        .unwrap_err();
+    assert_eq!(err, ContractError::CancelNotAllowed);
+
+    let msg = AuctionMsg {
+        denom: Denom::Native(ATOM.to_string()),
+        end_time: Some(Expiration::AtTime(start.plus_seconds(10 * 3600))),
+        cancellation: Some(Cancellation::BeforeEnd(Duration::Time(2 * 3600))),
+        ..Default::default()
+    };
+    let first = contract
+        .create_auction_with_msg(&mut app, &owner, &msg)
+        .unwrap();
+    let second = contract
+        .create_auction_with_msg(&mut app, &owner, &msg)
+        .unwrap();
+
+    app.update_block(|block| block.time = start.plus_seconds(7 * 3600));
+    contract.cancel(&mut app, &owner, first).unwrap();
+
+    app.update_block(|block| block.time = start.plus_seconds(8 * 3600));
+    let err = contract.cancel(&mut app, &owner, second).unwrap_err();
+    assert_eq!(err, ContractError::CancelNotAllowed);
+}
+
+#[test]
+fn pauser_freezes_operations_independently() {
+    let owner = Addr::unchecked("owner");
+    let pauser = Addr::unchecked("pauser");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract = BiddingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        &InstantiateMsg {
+            pauser: Some(pauser.to_string()),
+        },
+    )
+    .unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(300, ATOM))
+        .unwrap();
+    contract
+        .bid(&mut app, &bob, auction_id, &coins(400, ATOM))
+        .unwrap();
+
+    let err = contract
+        .pause(&mut app, &owner, Operation::Bid)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: pauser.to_string()
+        }
+    );
+
+    contract.pause(&mut app, &pauser, Operation::Bid).unwrap();
+    contract
+        .pause(&mut app, &pauser, Operation::Retract)
+        .unwrap();
+
+    let resp = contract.query_pause_state(&app).unwrap();
+    assert_eq!(
+        resp,
+        PauseStateResponse {
+            pauser: Some(pauser.clone()),
+            paused: PauseState {
+                bid: true,
+                close: false,
+                retract: true,
+            },
+        }
+    );
+
+    let err = contract
+        .bid(&mut app, &alice, auction_id, &coins(200, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Paused {
+            operation: Operation::Bid
+        }
+    );
+
+    // Queries keep answering while paused
+    assert_eq!(
+        contract.query_highest_bid(&app, auction_id).unwrap(),
+        BidResponse {
+            address: bob.clone(),
+            amount: Uint128::new(400),
+        }
+    );
+
+    contract.unpause(&mut app, &pauser, Operation::Bid).unwrap();
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(200, ATOM))
+        .unwrap();
+
+    contract.close(&mut app, &owner, auction_id).unwrap();
+
+    let err = contract
+        .retract(&mut app, &bob, auction_id, None)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Paused {
+            operation: Operation::Retract
+        }
+    );
+
+    contract
+        .unpause(&mut app, &pauser, Operation::Retract)
+        .unwrap();
+    contract.retract(&mut app, &bob, auction_id, None).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&bob).unwrap(),
+        coins(1_000, ATOM)
+    );
+
+    // Pausing bids stops commitments but not reveals, which would otherwise be forfeited
+    let start = app.block_info().time;
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                kind: Some(AuctionKind::Sealed {
+                    reveal_period: Duration::Time(100),
+                    forfeit: Decimal::percent(50),
+                }),
+                end_time: Some(Expiration::AtTime(start.plus_seconds(100))),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    contract
+        .commit(
+            &mut app,
+            &bob,
+            auction_id,
+            commitment_hash(&bob, Uint128::new(600), "bob"),
+            &coins(1_000, ATOM),
+        )
+        .unwrap();
+    contract.pause(&mut app, &pauser, Operation::Bid).unwrap();
+
+    let err = contract
+        .commit(
+            &mut app,
+            &alice,
+            auction_id,
+            commitment_hash(&alice, Uint128::new(500), "alice"),
+            &coins(500, ATOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Paused {
+            operation: Operation::Bid
+        }
+    );
+
+    app.update_block(|block| block.time = start.plus_seconds(150));
+    contract
+        .reveal(&mut app, &bob, auction_id, 600, "bob")
+        .unwrap();
+
+    app.update_block(|block| block.time = start.plus_seconds(200));
+    contract.close(&mut app, &owner, auction_id).unwrap();
+    contract.retract(&mut app, &bob, auction_id, None).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&bob).unwrap(),
+        coins(400, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(1_100, ATOM)
+    );
+}
+
+#[test]
+fn sudo_replaces_pauser() {
+    let owner = Addr::unchecked("owner");
+    let pauser = Addr::unchecked("pauser");
+
+    let mut app = App::default();
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+
+    let err = contract
+        .pause(&mut app, &pauser, Operation::Close)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: String::new()
+        }
+    );
+
+    contract
+        .sudo(
+            &mut app,
+            &SudoMsg::SetPauser {
+                pauser: Some(pauser.to_string()),
+            },
+        )
+        .unwrap();
+    contract.pause(&mut app, &pauser, Operation::Close).unwrap();
+    assert!(contract.query_pause_state(&app).unwrap().paused.close);
+
+    contract
+        .sudo(&mut app, &SudoMsg::SetPauser { pauser: None })
+        .unwrap();
+    assert_eq!(
+        contract.query_pause_state(&app).unwrap(),
+        PauseStateResponse {
+            pauser: None,
+            paused: PauseState::default(),
+        }
+    );
+}
+
+/// Hashes a pair of nodes the way the contract does, smaller one first
+fn hash_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().to_vec()
+}
+
+#[test]
+fn allowlisted_bidding() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in [&alice, &bob, &carol] {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    // Three leaves: alice and bob pair up, carol is paired with herself
+    let [alice_leaf, bob_leaf, carol_leaf] =
+        [&alice, &bob, &carol].map(|addr| Sha256::digest(addr.as_bytes()).to_vec());
+    let alice_bob = hash_pair(&alice_leaf, &bob_leaf);
+    let carol_carol = hash_pair(&carol_leaf, &carol_leaf);
+    let root = HexBinary::from(hash_pair(&alice_bob, &carol_carol));
+
+    let alice_proof = vec![HexBinary::from(bob_leaf), HexBinary::from(carol_carol)];
+    let carol_proof = vec![HexBinary::from(carol_leaf), HexBinary::from(alice_bob)];
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+
+    let err = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                allowlist: Some(HexBinary::from(b"not a root")),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidAllowlist);
+
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(ATOM.to_string()),
+                allowlist: Some(root.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert!(contract
+        .query_is_eligible(&app, auction_id, &alice, alice_proof.clone())
+        .unwrap());
+    assert!(!contract
+        .query_is_eligible(&app, auction_id, &bob, alice_proof.clone())
+        .unwrap());
+
+    let err = contract
+        .bid(&mut app, &alice, auction_id, &coins(100, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotAllowlisted);
+
+    let err = contract
+        .bid_with_proof(
+            &mut app,
+            &bob,
+            auction_id,
+            alice_proof.clone(),
+            &coins(100, ATOM),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotAllowlisted);
+
+    contract
+        .bid_with_proof(
+            &mut app,
+            &alice,
+            auction_id,
+            alice_proof.clone(),
+            &coins(100, ATOM),
+        )
+        .unwrap();
+    contract
+        .bid_with_proof(&mut app, &carol, auction_id, carol_proof, &coins(200, ATOM))
+        .unwrap();
+
+    // Rotating the root to a single leaf tree shuts out everyone else
+    let err = contract
+        .update_allowlist(&mut app, &alice, auction_id, None)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+
+    let bob_root = HexBinary::from(Sha256::digest(bob.as_bytes()).as_slice());
+    contract
+        .update_allowlist(&mut app, &owner, auction_id, bob_root)
+        .unwrap();
+
+    let err = contract
+        .bid_with_proof(&mut app, &alice, auction_id, alice_proof, &coins(300, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotAllowlisted);
+    contract
+        .bid_with_proof(&mut app, &bob, auction_id, vec![], &coins(300, ATOM))
+        .unwrap();
+
+    contract
+        .update_allowlist(&mut app, &owner, auction_id, None)
+        .unwrap();
+    assert!(contract
+        .query_is_eligible(&app, auction_id, &alice, vec![])
+        .unwrap());
+    assert!(!contract
+        .query_is_eligible(&app, auction_id, &owner, vec![])
+        .unwrap());
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(300, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        contract.query_highest_bid(&app, auction_id).unwrap(),
+        BidResponse {
+            address: alice,
+            amount: Uint128::new(400),
+        }
+    );
+}
+
+#[test]
+fn denied_bidders_keep_their_escrow() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+
+    let mut app = App::new(|router, _api, storage| {
+        for bidder in [&alice, &bob] {
+            router
+                .bank
+                .init_balance(storage, bidder, coins(1_000, ATOM))
+                .unwrap();
+        }
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction(&mut app, &owner, None, ATOM, None)
+        .unwrap();
+
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(300, ATOM))
+        .unwrap();
+
+    let err = contract
+        .deny(&mut app, &bob, auction_id, &[&alice])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+
+    // The leader would still win, so they cannot be denied until outbid
+    let err = contract
+        .deny(&mut app, &owner, auction_id, &[&carol, &alice])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DenyLeader {
+            address: alice.to_string()
+        }
+    );
+    let resp = contract
+        .query_denied_addresses(&app, auction_id, None, None)
+        .unwrap();
+    assert!(resp.addresses.is_empty());
+
+    contract
+        .bid(&mut app, &bob, auction_id, &coins(400, ATOM))
+        .unwrap();
+    let err = contract
+        .deny(&mut app, &owner, auction_id, &[&bob])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DenyLeader {
+            address: bob.to_string()
+        }
+    );
+
+    contract
+        .deny(&mut app, &owner, auction_id, &[&carol, &alice, &dave])
+        .unwrap();
+
+    let resp = contract
+        .query_denied_addresses(&app, auction_id, None, 2)
+        .unwrap();
+    assert_eq!(resp.addresses, vec![alice.clone(), carol.clone()]);
+    let resp = contract
+        .query_denied_addresses(&app, auction_id, &carol, None)
+        .unwrap();
+    assert_eq!(resp.addresses, vec![dave.clone()]);
+
+    let err = contract
+        .bid(&mut app, &alice, auction_id, &coins(100, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Denied {
+            address: alice.to_string()
+        }
+    );
+
+    contract
+        .allow(&mut app, &owner, auction_id, &[&carol, &dave])
+        .unwrap();
+    let resp = contract
+        .query_denied_addresses(&app, auction_id, None, None)
+        .unwrap();
+    assert_eq!(resp.addresses, vec![alice.clone()]);
+
+    contract.close(&mut app, &owner, auction_id).unwrap();
+
+    // Being denied does not stand in the way of getting the escrow back
+    contract
+        .retract(&mut app, &alice, auction_id, None)
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&alice).unwrap(),
+        coins(1_000, ATOM)
+    );
+}
+
+#[test]
+fn bids_across_accepted_denoms() {
+    const USDC: &str = "usdc";
+    const OSMO_USDC: &str = "ibc/usdc-osmo";
+
+    let owner = Addr::unchecked("owner");
+    let treasury = Addr::unchecked("treasury");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &alice,
+                vec![coin(1_000, OSMO_USDC), coin(1_000, USDC)],
+            )
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, vec![coin(100, ATOM), coin(100, "btc")])
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+    let auction_id = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                denom: Denom::Native(USDC.to_string()),
+                commission: Some(Decimal::percent(10)),
+                fee_mode: Some(FeeMode::OnSettlement),
+                fee_recipient: Some(treasury.to_string()),
+                accepted_denoms: Some(vec![
+                    DenomRate {
+                        denom: OSMO_USDC.to_string(),
+                        rate: Decimal::one(),
+                    },
+                    DenomRate {
+                        denom: ATOM.to_string(),
+                        rate: Decimal::from_ratio(8u128, 1u128),
+                    },
+                ]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let err = contract
+        .bid(
+            &mut app,
+            &bob,
+            auction_id,
+            &[coin(20, ATOM), coin(1, "btc")],
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFunds);
+
+    contract
+        .bid(
+            &mut app,
+            &alice,
+            auction_id,
+            &[coin(100, OSMO_USDC), coin(50, USDC)],
+        )
+        .unwrap();
+    contract
+        .bid(&mut app, &bob, auction_id, &coins(20, ATOM))
+        .unwrap();
+    assert_eq!(
+        contract.query_highest_bid(&app, auction_id).unwrap(),
+        BidResponse {
+            address: bob.clone(),
+            amount: Uint128::new(160),
+        }
+    );
+
+    contract
+        .bid(&mut app, &alice, auction_id, &coins(30, OSMO_USDC))
+        .unwrap();
+    assert_eq!(
+        contract.query_top_bids(&app, auction_id, 2).unwrap().bids,
+        vec![
+            BidResponse {
+                address: alice.clone(),
+                amount: Uint128::new(180),
+            },
+            BidResponse {
+                address: bob.clone(),
+                amount: Uint128::new(160),
+            },
+        ]
+    );
+
+    contract.close(&mut app, &owner, auction_id).unwrap();
+
+    // The winning 180 is paid in the coins alice sent, each split between fee and proceeds
+    assert_eq!(
+        app.wrap().query_all_balances(&treasury).unwrap(),
+        vec![coin(13, OSMO_USDC), coin(5, USDC)]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        vec![coin(117, OSMO_USDC), coin(45, USDC)]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&alice).unwrap(),
+        vec![coin(870, OSMO_USDC), coin(950, USDC)]
+    );
+
+    contract.retract(&mut app, &bob, auction_id, None).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&bob).unwrap(),
+        vec![coin(100, ATOM), coin(100, "btc")]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn accepted_denoms_round_in_favour_of_the_owner() {
+    const USDC: &str = "usdc";
+
+    let owner = Addr::unchecked("owner");
+    let carol = Addr::unchecked("carol");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &carol, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let code_id = BiddingContract::store_code(&mut app);
+    let contract =
+        BiddingContract::instantiate(&mut app, code_id, &owner, "Bidding contract").unwrap();
+
+    let atom_at = |rate: u128| DenomRate {
+        denom: ATOM.to_string(),
+        rate: Decimal::from_ratio(rate, 1u128),
+    };
+    let usdc_auction = |accepted_denoms: Vec<DenomRate>| AuctionMsg {
+        denom: Denom::Native(USDC.to_string()),
+        buy_now: Some(Uint128::new(100)),
+        accepted_denoms: Some(accepted_denoms),
+        ..Default::default()
+    };
+
+    for accepted_denoms in [
+        vec![atom_at(0)],
+        vec![atom_at(8), atom_at(4)],
+        vec![DenomRate {
+            denom: USDC.to_string(),
+            rate: Decimal::one(),
+        }],
+    ] {
+        let err = contract
+            .create_auction_with_msg(&mut app, &owner, &usdc_auction(accepted_denoms))
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidDenoms);
+    }
+
+    let end_time = Expiration::AtTime(app.block_info().time.plus_seconds(3600));
+    let err = contract
+        .create_auction_with_msg(
+            &mut app,
+            &owner,
+            &AuctionMsg {
+                kind: Some(AuctionKind::Sealed {
+                    reveal_period: Duration::Time(3600),
+                    forfeit: Decimal::zero(),
+                }),
+                buy_now: None,
+                end_time: Some(end_time),
+                ..usdc_auction(vec![atom_at(8)])
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidDenoms);
+
+    let auction_id = contract
+        .create_auction_with_msg(&mut app, &owner, &usdc_auction(vec![atom_at(8)]))
+        .unwrap();
+
+    // 15 ATOM are worth 120; the price of 100 takes 13 of them and the other 2 come back
+    contract
+        .bid(&mut app, &carol, auction_id, &coins(15, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(13, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&carol).unwrap(),
+        coins(87, ATOM)
+    );
+    assert_eq!(contract.query_unrefunded_bids(&app, auction_id).unwrap(), 0);
+}
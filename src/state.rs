@@ -1,26 +1,264 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Decimal, Empty, HexBinary, Timestamp, Uint128};
+use cw20::Denom;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, KeyDeserialize, Map, MultiIndex};
+use cw_utils::{Duration, Expiration, Scheduled};
 
 #[cw_serde]
 pub enum Status {
     Open,
     Closed,
+    /// Ended below the reserve price; every bidder can retract in full
+    Failed,
+    /// Withdrawn without a sale; every bidder can retract in full
+    Cancelled,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum AuctionKind {
+    /// Ascending price, the highest bid when the auction ends wins
+    #[default]
+    English,
+    /// Price falls from `start_price` to `floor_price` between the start and end
+    /// time, and the first bid paying the current price wins
+    Dutch {
+        start_price: Uint128,
+        floor_price: Uint128,
+        /// Seconds between price drops; the price falls continuously when unset
+        step: Option<u64>,
+    },
+    /// Bidders commit to a hidden amount until the end time and reveal it during
+    /// `reveal_period` afterwards; the highest revealed bid wins
+    Sealed {
+        reveal_period: Duration,
+        /// Share of the deposit handed to the owner when a commitment is never revealed
+        forfeit: Decimal,
+    },
+}
+
+/// What the winner of an auction pays
+#[cw_serde]
+#[derive(Default)]
+pub enum Settlement {
+    /// The winning bid itself
+    #[default]
+    FirstPrice,
+    /// The runner-up bid, or the reserve or opening price when that is higher (Vickrey)
+    SecondPrice,
+}
+
+/// When the commission is charged
+#[cw_serde]
+#[derive(Default)]
+pub enum FeeMode {
+    /// Skimmed off every bid as it comes in
+    #[default]
+    PerBid,
+    /// Taken once from the winning amount when the auction closes; losing bids pay nothing
+    OnSettlement,
+}
+
+/// When the owner may still call the auction off
+#[cw_serde]
+#[derive(Default)]
+pub enum Cancellation {
+    #[default]
+    Anytime,
+    BeforeFirstBid,
+    /// Until this long before the end time
+    BeforeEnd(Duration),
+}
+
+/// How much a new bid has to add on top of the current leader
+#[cw_serde]
+pub enum BidIncrement {
+    Amount(Uint128),
+    Percent(Decimal),
+}
+
+/// Another native denom bids can be placed in, each unit counting as `rate` of the auction denom
+#[cw_serde]
+pub struct DenomRate {
+    pub denom: String,
+    pub rate: Decimal,
 }
 
 #[cw_serde]
 pub struct Config {
     pub owner: Addr,
-    pub denom: String,
-    pub commission: u128,
+    pub kind: AuctionKind,
+    pub settlement: Settlement,
+    pub denom: Denom,
+    /// Fee rate, applied as described by `fee_mode`
+    pub commission: Decimal,
+    pub fee_mode: FeeMode,
+    /// Receives the fees, the owner when unset
+    pub fee_recipient: Option<Addr>,
+    pub reserve_price: Option<Uint128>,
+    pub opening_price: Uint128,
+    pub min_increment: Option<BidIncrement>,
+    pub buy_now: Option<Uint128>,
+    pub start_time: Option<Scheduled>,
+    pub end_time: Expiration,
+    pub extension_window: Option<Duration>,
+    pub keeper_reward: Decimal,
+    pub cancellation: Cancellation,
+    /// Merkle root of the addresses allowed to bid; anyone can bid when unset
+    pub allowlist: Option<HexBinary>,
+    pub accepted_denoms: Vec<DenomRate>,
+}
+
+#[cw_serde]
+pub struct StandingBid {
+    pub bidder: Addr,
+    pub amount: Uint128,
 }
 
 #[cw_serde]
 pub struct State {
     pub current_status: Status,
-    pub highest_bid: Option<(Addr, Uint128)>,
+    pub highest_bid: Option<StandingBid>,
+    /// Best bid from anyone other than the leader, sets the price of second-price auctions
+    pub runner_up: Option<StandingBid>,
+    /// Effective deadline, pushed out by bids landing inside the extension window
+    pub end_time: Expiration,
+}
+
+/// NFT sold by an auction, held by the contract once `escrowed`
+#[cw_serde]
+pub struct Lot {
+    pub collection: Addr,
+    pub token_id: String,
+    pub escrowed: bool,
+}
+
+/// Ownership transfer in progress, or given up altogether
+#[cw_serde]
+#[derive(Default)]
+pub struct Ownership {
+    /// Becomes the owner once it accepts
+    pub pending_owner: Option<Addr>,
+    /// The owner keeps the proceeds but can no longer manage the auction
+    pub renounced: bool,
+}
+
+/// Operations the pauser can freeze one at a time
+#[cw_serde]
+pub enum Operation {
+    /// Placing and committing bids; reveals go on so no commitment is forfeited over a pause
+    Bid,
+    /// Ending auctions through `Close` or `Settle`
+    Close,
+    /// Paying bids back through `Retract` or `RefundLosers`
+    Retract,
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::Bid => write!(f, "bid"),
+            Operation::Close => write!(f, "close"),
+            Operation::Retract => write!(f, "retract"),
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseState {
+    pub bid: bool,
+    pub close: bool,
+    pub retract: bool,
+}
+
+/// Hash a sealed bidder committed to, see `contract::commitment_hash`
+#[cw_serde]
+pub struct Commitment {
+    pub commitment: HexBinary,
+    pub revealed: bool,
+}
+
+/// One accepted bid, in the order it came in
+#[cw_serde]
+pub struct BidRecord {
+    pub auction_id: u64,
+    pub seq: u64,
+    pub bidder: Addr,
+    /// Amount added by this bid
+    pub increment: Uint128,
+    /// Bidder's total once this bid was accepted
+    pub total: Uint128,
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+pub struct BidHistoryIndexes<'a> {
+    pub bidder: MultiIndex<'a, (u64, Addr), BidRecord, (u64, u64)>,
+}
+
+impl<'a> IndexList<BidRecord> for BidHistoryIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<BidRecord>> + '_> {
+        let v: Vec<&dyn Index<BidRecord>> = vec![&self.bidder];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Bid records keyed by `(auction_id, seq)`
+pub fn bid_history<'a>() -> IndexedMap<'a, (u64, u64), BidRecord, BidHistoryIndexes<'a>> {
+    let indexes = BidHistoryIndexes {
+        bidder: MultiIndex::new(
+            |_pk, record| (record.auction_id, record.bidder.clone()),
+            "bid_history",
+            "bid_history__bidder",
+        ),
+    };
+
+    IndexedMap::new("bid_history", indexes)
+}
+
+pub struct BidIndexes<'a> {
+    /// `(auction_id, amount)`, ranks the bidders of an auction
+    pub amount: MultiIndex<'a, (u64, u128), Uint128, (u64, Addr)>,
+}
+
+impl<'a> IndexList<Uint128> for BidIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Uint128>> + '_> {
+        let v: Vec<&dyn Index<Uint128>> = vec![&self.amount];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Funds each bidder holds in escrow, keyed by `(auction_id, bidder)`
+pub fn bids<'a>() -> IndexedMap<'a, (u64, &'a Addr), Uint128, BidIndexes<'a>> {
+    let indexes = BidIndexes {
+        amount: MultiIndex::new(
+            |pk, amount| {
+                // Keys are only ever written by the contract itself
+                let (auction_id, _) = <(u64, Addr)>::from_slice(pk).expect("valid bid key");
+                (auction_id, amount.u128())
+            },
+            "auction_bids",
+            "auction_bids__amount",
+        ),
+    };
+
+    IndexedMap::new("auction_bids", indexes)
 }
 
-pub const STATE: Item<State> = Item::new("state");
-pub const CONFIG: Item<Config> = Item::new("config");
-pub const BIDS: Map<&Addr, Uint128> = Map::new("bids");
+pub const AUCTION_COUNT: Item<u64> = Item::new("auction_count");
+/// May pause and unpause operations across every auction
+pub const PAUSER: Item<Addr> = Item::new("pauser");
+pub const PAUSED: Item<PauseState> = Item::new("paused");
+/// Highest commission any auction may charge, set through sudo; 100% when unset
+pub const FEE_CAP: Item<Decimal> = Item::new("fee_cap");
+pub const STATES: Map<u64, State> = Map::new("auction_states");
+pub const CONFIGS: Map<u64, Config> = Map::new("auction_configs");
+pub const LOTS: Map<u64, Lot> = Map::new("auction_lots");
+pub const OWNERSHIPS: Map<u64, Ownership> = Map::new("auction_ownerships");
+pub const COMMITMENTS: Map<(u64, &Addr), Commitment> = Map::new("auction_commitments");
+/// Coins of the accepted denoms held for a bidder; their value is part of the `bids` amount,
+/// the rest of which is held in the auction denom
+pub const ESCROWS: Map<(u64, &Addr, &str), Uint128> = Map::new("auction_escrows");
+/// Addresses the owner has barred from bidding on the auction
+pub const DENIED: Map<(u64, &Addr), Empty> = Map::new("auction_denied");